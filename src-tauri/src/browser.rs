@@ -1,7 +1,17 @@
+use crate::db::Database;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+/// Settings key for the configured profiles, stored as a JSON list
+pub const BROWSER_PROFILES_SETTING: &str = "browser_profiles";
+
+/// Microseconds between the WebKit epoch (1601-01-01) Chromium uses and the Unix epoch
+const WEBKIT_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+/// Seconds between the Core Data epoch (2001-01-01) Safari uses and the Unix epoch
+const CORE_DATA_EPOCH_OFFSET_SECONDS: i64 = 978_307_200;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BrowserVisit {
     pub url: String,
@@ -10,13 +20,260 @@ pub struct BrowserVisit {
     pub visit_count: i32,
 }
 
-/// Auto-detect Zen browser profile path
-pub fn auto_detect_zen_profile() -> Result<Option<String>, String> {
-    let home = std::env::var("HOME")
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserKind {
+    Zen,
+    Chrome,
+    Arc,
+    Brave,
+    Edge,
+    Safari,
+}
+
+impl BrowserKind {
+    pub const ALL: [BrowserKind; 6] = [
+        BrowserKind::Zen,
+        BrowserKind::Chrome,
+        BrowserKind::Arc,
+        BrowserKind::Brave,
+        BrowserKind::Edge,
+        BrowserKind::Safari,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BrowserKind::Zen => "Zen",
+            BrowserKind::Chrome => "Chrome",
+            BrowserKind::Arc => "Arc",
+            BrowserKind::Brave => "Brave",
+            BrowserKind::Edge => "Edge",
+            BrowserKind::Safari => "Safari",
+        }
+    }
+
+    fn is_chromium(&self) -> bool {
+        !matches!(self, BrowserKind::Zen | BrowserKind::Safari)
+    }
+
+    /// History database inside a profile directory
+    fn history_file(&self) -> &'static str {
+        match self {
+            BrowserKind::Zen => "places.sqlite",
+            BrowserKind::Safari => "History.db",
+            _ => "History",
+        }
+    }
+
+    /// Directories holding the browser's profiles, on macOS and Linux
+    fn profiles_dirs(&self, home: &Path) -> Vec<PathBuf> {
+        let relative: &[&str] = match self {
+            BrowserKind::Zen => &["Library/Application Support/zen/Profiles", ".zen"],
+            BrowserKind::Chrome => &[
+                "Library/Application Support/Google/Chrome",
+                ".config/google-chrome",
+            ],
+            BrowserKind::Arc => &["Library/Application Support/Arc/User Data"],
+            BrowserKind::Brave => &[
+                "Library/Application Support/BraveSoftware/Brave-Browser",
+                ".config/BraveSoftware/Brave-Browser",
+            ],
+            BrowserKind::Edge => &[
+                "Library/Application Support/Microsoft Edge",
+                ".config/microsoft-edge",
+            ],
+            // Profiles other than the default one, which lives in Library/Safari itself
+            BrowserKind::Safari => &[
+                "Library/Safari/Profiles",
+                "Library/Containers/com.apple.Safari/Data/Library/Safari/Profiles",
+            ],
+        };
+        relative.iter().map(|dir| home.join(dir)).collect()
+    }
+}
+
+/// A browser profile whose history can be ingested
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrowserProfile {
+    pub browser: BrowserKind,
+    pub name: String, // As shown in the browser's profile menu, or the directory name
+    pub path: String, // Profile directory
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool, // Disabled profiles stay configured but aren't synced
+    #[serde(default)]
+    pub label: Option<String>, // Such as "work" or "personal"; recorded on each browser event
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl BrowserProfile {
+    pub fn new(browser: BrowserKind, path: &Path) -> Self {
+        BrowserProfile {
+            browser,
+            name: profile_dir_name(path),
+            path: path.to_string_lossy().to_string(),
+            enabled: true,
+            label: None,
+        }
+    }
+}
+
+/// Configured profiles that are enabled for sync
+pub fn enabled_profiles(db: &Database) -> rusqlite::Result<Vec<BrowserProfile>> {
+    Ok(load_profiles(db)?
+        .into_iter()
+        .filter(|profile| profile.enabled)
+        .collect())
+}
+
+/// Every configured profile, enabled or not
+pub fn load_profiles(db: &Database) -> rusqlite::Result<Vec<BrowserProfile>> {
+    match db.get_setting(BROWSER_PROFILES_SETTING)? {
+        Some(value) => serde_json::from_str(&value).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        }),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Replace the configured profiles. A profile directory can only be configured once.
+pub fn save_profiles(db: &Database, profiles: &[BrowserProfile]) -> rusqlite::Result<()> {
+    for (index, profile) in profiles.iter().enumerate() {
+        if profiles[..index].iter().any(|p| p.path == profile.path) {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Browser profile '{}' is configured twice",
+                profile.path
+            )));
+        }
+    }
+    let value = serde_json::to_string(profiles)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    db.set_setting(BROWSER_PROFILES_SETTING, &value)
+}
+
+/// Every profile with a history database, for all supported browsers
+pub fn discover_profiles() -> Result<Vec<BrowserProfile>, String> {
+    let home = home_dir()?;
+    Ok(BrowserKind::ALL
+        .iter()
+        .flat_map(|browser| discover_browser_profiles(*browser, &home))
+        .collect())
+}
+
+fn discover_browser_profiles(browser: BrowserKind, home: &Path) -> Vec<BrowserProfile> {
+    let mut profiles = Vec::new();
+    let safari_default = home.join("Library/Safari");
+    if browser == BrowserKind::Safari && safari_default.join(browser.history_file()).is_file() {
+        profiles.push(BrowserProfile {
+            name: "Default".to_string(),
+            ..BrowserProfile::new(browser, &safari_default)
+        });
+    }
+    for dir in browser.profiles_dirs(home) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let names = if browser.is_chromium() {
+            chromium_profile_names(&dir)
+        } else {
+            serde_json::Map::new()
+        };
+
+        let mut found: Vec<BrowserProfile> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.join(browser.history_file()).is_file())
+            .map(|path| BrowserProfile {
+                name: chromium_profile_name(&names, &path)
+                    .unwrap_or_else(|| profile_dir_name(&path)),
+                ..BrowserProfile::new(browser, &path)
+            })
+            .collect();
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+        profiles.extend(found);
+    }
+    profiles
+}
+
+/// The profile in a directory chosen by hand, recognised by its history database.
+/// Chromium browsers share a format, so the browser is told apart by where the directory is.
+pub fn profile_at(path: &Path) -> Result<BrowserProfile, String> {
+    let home = home_dir()?;
+    let candidates: Vec<BrowserKind> = BrowserKind::ALL
+        .into_iter()
+        .filter(|browser| path.join(browser.history_file()).is_file())
+        .collect();
+    let browser = candidates
+        .iter()
+        .find(|browser| {
+            browser
+                .profiles_dirs(&home)
+                .iter()
+                .any(|dir| path.starts_with(dir))
+                || (**browser == BrowserKind::Safari && path == home.join("Library/Safari"))
+        })
+        .or_else(|| candidates.first())
+        .copied()
+        .ok_or_else(|| format!("No browser history found in {}", path.display()))?;
+
+    let name = path
+        .parent()
+        .filter(|_| browser.is_chromium())
+        .and_then(|user_data_dir| {
+            chromium_profile_name(&chromium_profile_names(user_data_dir), path)
+        });
+
+    Ok(BrowserProfile {
+        name: name.unwrap_or_else(|| profile_dir_name(path)),
+        ..BrowserProfile::new(browser, path)
+    })
+}
+
+/// Profile display names from Chromium's `Local State`, keyed by profile directory
+fn chromium_profile_names(user_data_dir: &Path) -> serde_json::Map<String, serde_json::Value> {
+    std::fs::read_to_string(user_data_dir.join("Local State"))
+        .ok()
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        .and_then(|state| state.pointer("/profile/info_cache")?.as_object().cloned())
+        .unwrap_or_default()
+}
+
+fn chromium_profile_name(
+    names: &serde_json::Map<String, serde_json::Value>,
+    profile_dir: &Path,
+) -> Option<String> {
+    let dir_name = profile_dir.file_name()?.to_string_lossy();
+    names
+        .get(dir_name.as_ref())?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Directory name without the random prefix Firefox-based browsers add ("x1y2.Default")
+fn profile_dir_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match name.split_once('.') {
+        Some((_, rest)) if !rest.is_empty() => rest.to_string(),
+        _ => name,
+    }
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    std::env::var("HOME")
         .or_else(|_| std::env::var("USERPROFILE"))
-        .map_err(|e| format!("Failed to get home directory: {}", e))?;
+        .map(PathBuf::from)
+        .map_err(|e| format!("Failed to get home directory: {}", e))
+}
 
-    let profiles_dir = PathBuf::from(home).join("Library/Application Support/zen/Profiles");
+/// Auto-detect Zen browser profile path
+pub fn auto_detect_zen_profile() -> Result<Option<String>, String> {
+    let profiles_dir = home_dir()?.join("Library/Application Support/zen/Profiles");
 
     if !profiles_dir.exists() {
         return Ok(None);
@@ -54,27 +311,28 @@ pub fn auto_detect_zen_profile() -> Result<Option<String>, String> {
 
 /// Get browser visits between specific timestamps (in seconds)
 pub fn get_browser_visits_range(
+    browser: BrowserKind,
     profile_path: &str,
     start_timestamp: i64,
     end_timestamp: i64,
 ) -> Result<Vec<BrowserVisit>, String> {
-    let profile_path = PathBuf::from(profile_path);
-    let places_path = profile_path.join("places.sqlite");
+    let history_path = PathBuf::from(profile_path).join(browser.history_file());
 
     if std::env::var("TRACEBACK_DEBUG").is_ok() {
-        eprintln!("[Browser:DEBUG] Reading from: {}", places_path.display());
+        eprintln!("[Browser:DEBUG] Reading from: {}", history_path.display());
     }
 
-    // Read directly from the places.sqlite file
+    // Read directly from the history file
     // SQLite can read from locked files in read-only mode
-    query_visits(&places_path, start_timestamp, end_timestamp)
+    match browser {
+        BrowserKind::Zen => query_visits(&history_path, start_timestamp, end_timestamp),
+        BrowserKind::Safari => query_safari_visits(&history_path, start_timestamp, end_timestamp),
+        _ => query_chromium_visits(&history_path, start_timestamp, end_timestamp),
+    }
 }
 
-fn query_visits(
-    db_path: &Path,
-    start_timestamp: i64,
-    end_timestamp: i64,
-) -> Result<Vec<BrowserVisit>, String> {
+/// Open a browser history database without taking locks, checking it has `table`
+fn open_history(db_path: &Path, table: &str) -> Result<Connection, String> {
     let debug = std::env::var("TRACEBACK_DEBUG").is_ok();
 
     if debug {
@@ -82,7 +340,7 @@ fn query_visits(
     }
 
     // Open in read-only mode with immutable flag
-    // This allows reading even when Firefox/Zen has the file locked
+    // This allows reading even when the browser has the file locked
     let db_uri = format!("file:{}?mode=ro&immutable=1", db_path.display());
 
     if debug {
@@ -90,9 +348,8 @@ fn query_visits(
     }
 
     let conn =
-        Connection::open(&db_uri).map_err(|e| format!("Failed to open places database: {}", e))?;
+        Connection::open(&db_uri).map_err(|e| format!("Failed to open history database: {}", e))?;
 
-    // Verify this is a Firefox/Zen database
     if debug {
         eprintln!("[Browser:DEBUG] Checking database schema...");
     }
@@ -109,15 +366,28 @@ fn query_visits(
         eprintln!("[Browser:DEBUG] Found tables: {:?}", tables);
     }
 
-    if !tables.contains(&"moz_places".to_string()) {
-        return Err("Database does not contain moz_places table. This may not be a Firefox/Zen places.sqlite file.".to_string());
+    if !tables.iter().any(|name| name == table) {
+        return Err(format!(
+            "Database does not contain a {} table. This may not be a browser history file.",
+            table
+        ));
     }
 
+    Ok(conn)
+}
+
+fn query_visits(
+    db_path: &Path,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> Result<Vec<BrowserVisit>, String> {
+    let conn = open_history(db_path, "moz_places")?;
+
     // Convert to microseconds for Firefox
     let start_micros = start_timestamp * 1_000_000;
     let end_micros = end_timestamp * 1_000_000;
 
-    if debug {
+    if std::env::var("TRACEBACK_DEBUG").is_ok() {
         eprintln!(
             "[Browser:DEBUG] Querying visits between {} and {} (microseconds)",
             start_micros, end_micros
@@ -135,55 +405,84 @@ fn query_visits(
          INNER JOIN moz_historyvisits ON moz_places.id = moz_historyvisits.place_id
          WHERE moz_historyvisits.visit_date >= ?1
            AND moz_historyvisits.visit_date <= ?2
-           -- Browser internal pages
-           AND moz_places.url NOT LIKE 'chrome://%'
-           AND moz_places.url NOT LIKE 'about:%'
-           AND moz_places.url NOT LIKE 'moz-extension://%'
-           -- Localhost and local development
-           AND moz_places.url NOT LIKE 'http://localhost%'
-           AND moz_places.url NOT LIKE 'https://localhost%'
-           AND moz_places.url NOT LIKE 'http://127.0.0.1%'
-           AND moz_places.url NOT LIKE 'https://127.0.0.1%'
-           AND moz_places.url NOT LIKE '%.local/%'
-           -- Authentication & OAuth flows
-           AND moz_places.url NOT LIKE '%/auth/%'
-           AND moz_places.url NOT LIKE '%/oauth/%'
-           AND moz_places.url NOT LIKE '%/login%'
-           AND moz_places.url NOT LIKE '%/signin%'
-           AND moz_places.url NOT LIKE '%/sso/%'
-           AND moz_places.url NOT LIKE '%/saml/%'
-           AND moz_places.url NOT LIKE '%/authorize%'
-           AND moz_places.url NOT LIKE '%/callback%'
-           -- Tokens and credentials in URL params
-           AND moz_places.url NOT LIKE '%access_token=%'
-           AND moz_places.url NOT LIKE '%id_token=%'
-           AND moz_places.url NOT LIKE '%refresh_token=%'
-           AND moz_places.url NOT LIKE '%api_key=%'
-           AND moz_places.url NOT LIKE '%apikey=%'
-           AND moz_places.url NOT LIKE '%secret=%'
-           AND moz_places.url NOT LIKE '%password=%'
-           AND moz_places.url NOT LIKE '%session_id=%'
-           -- Password & security pages
-           AND moz_places.url NOT LIKE '%/password/%'
-           AND moz_places.url NOT LIKE '%/security/%'
-           AND moz_places.url NOT LIKE '%/2fa/%'
-           AND moz_places.url NOT LIKE '%/mfa/%'
-           -- Payment & checkout
-           AND moz_places.url NOT LIKE '%/checkout%'
-           AND moz_places.url NOT LIKE '%/payment%'
-           AND moz_places.url NOT LIKE '%/billing%'
-           -- Admin panels
-           AND moz_places.url NOT LIKE '%/admin/%'
-           AND moz_places.url NOT LIKE '%/wp-admin/%'
-           -- Email clients (specific message URLs)
-           AND moz_places.url NOT LIKE '%mail.google.com/mail/u/%/#%'
-           AND moz_places.url NOT LIKE '%outlook.live.com/mail/%/inbox/id/%'
          ORDER BY moz_historyvisits.visit_date DESC",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
+    collect_visits(&mut stmt, start_micros, end_micros)
+}
+
+/// Visits from a Chromium `History` database. Its timestamps count microseconds from
+/// 1601-01-01 and are converted to Unix microseconds.
+fn query_chromium_visits(
+    db_path: &Path,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> Result<Vec<BrowserVisit>, String> {
+    let conn = open_history(db_path, "visits")?;
+
+    let start_micros = start_timestamp * 1_000_000 + WEBKIT_EPOCH_OFFSET_MICROS;
+    let end_micros = end_timestamp * 1_000_000 + WEBKIT_EPOCH_OFFSET_MICROS;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT
+            urls.url,
+            NULLIF(urls.title, ''),
+            visits.visit_time - {},
+            urls.visit_count
+         FROM urls
+         INNER JOIN visits ON urls.id = visits.url
+         WHERE visits.visit_time >= ?1
+           AND visits.visit_time <= ?2
+         ORDER BY visits.visit_time DESC",
+            WEBKIT_EPOCH_OFFSET_MICROS
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    collect_visits(&mut stmt, start_micros, end_micros)
+}
+
+/// Visits from Safari's `History.db`. Visit times are seconds (with a fraction) since
+/// 2001-01-01, and titles are recorded per visit rather than per page.
+/// Reading it on macOS needs Full Disk Access.
+fn query_safari_visits(
+    db_path: &Path,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> Result<Vec<BrowserVisit>, String> {
+    let conn = open_history(db_path, "history_visits")?;
+
+    let start = start_timestamp - CORE_DATA_EPOCH_OFFSET_SECONDS;
+    let end = end_timestamp - CORE_DATA_EPOCH_OFFSET_SECONDS;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT
+            history_items.url,
+            NULLIF(history_visits.title, ''),
+            CAST((history_visits.visit_time + {}) * 1000000 AS INTEGER),
+            history_items.visit_count
+         FROM history_items
+         INNER JOIN history_visits ON history_items.id = history_visits.history_item
+         WHERE history_visits.visit_time >= ?1
+           AND history_visits.visit_time <= ?2
+         ORDER BY history_visits.visit_time DESC",
+            CORE_DATA_EPOCH_OFFSET_SECONDS
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    collect_visits(&mut stmt, start, end)
+}
+
+/// Run a visits query selecting url, title, Unix microseconds and visit count
+fn collect_visits(
+    stmt: &mut rusqlite::Statement,
+    start: i64,
+    end: i64,
+) -> Result<Vec<BrowserVisit>, String> {
     let visits = stmt
-        .query_map(rusqlite::params![start_micros, end_micros], |row| {
+        .query_map(rusqlite::params![start, end], |row| {
             Ok(BrowserVisit {
                 url: row.get(0)?,
                 title: row.get(1)?,
@@ -197,3 +496,171 @@ fn query_visits(
 
     Ok(visits)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::BrowserFilter;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("traceback-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn chromium_history(path: &Path, visits: &[(&str, &str, i64)]) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT, title TEXT, visit_count INTEGER);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER);",
+        )
+        .unwrap();
+        for (id, (url, title, unix_seconds)) in visits.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO urls (id, url, title, visit_count) VALUES (?1, ?2, ?3, 1)",
+                rusqlite::params![id as i64, url, title],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO visits (url, visit_time) VALUES (?1, ?2)",
+                rusqlite::params![
+                    id as i64,
+                    unix_seconds * 1_000_000 + WEBKIT_EPOCH_OFFSET_MICROS
+                ],
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_chromium_visits_use_webkit_epoch() {
+        let profile = temp_dir();
+        chromium_history(
+            &profile.join("History"),
+            &[
+                ("https://github.com/a/b/pull/1", "Fix login", 1_700_000_100),
+                ("https://example.com/login?next=/", "Sign in", 1_700_000_200),
+                ("https://docs.rs/serde", "", 1_700_000_300),
+                ("https://old.example.com/", "Too early", 1_600_000_000),
+            ],
+        );
+
+        let visits = get_browser_visits_range(
+            BrowserKind::Chrome,
+            &profile.to_string_lossy(),
+            1_700_000_000,
+            1_700_001_000,
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&profile).ok();
+
+        // Sign-in pages are read here and dropped later by the privacy filter
+        assert_eq!(visits.len(), 3);
+        assert_eq!(visits[0].url, "https://docs.rs/serde");
+        assert_eq!(visits[0].title, None);
+        assert_eq!(visits[0].visit_date, 1_700_000_300_000_000);
+        assert!(!BrowserFilter::default().includes(&visits[1]));
+        assert_eq!(visits[2].title.as_deref(), Some("Fix login"));
+    }
+
+    #[test]
+    fn test_safari_visits_use_core_data_epoch() {
+        let profile = temp_dir();
+        let conn = Connection::open(profile.join("History.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE history_items (id INTEGER PRIMARY KEY, url TEXT, visit_count INTEGER);
+             CREATE TABLE history_visits (id INTEGER PRIMARY KEY, history_item INTEGER,
+                 visit_time REAL, title TEXT);
+             INSERT INTO history_items VALUES (1, 'https://linear.app/team/issue/ENG-1', 3);
+             INSERT INTO history_items VALUES (2, 'https://bank.example.com/payment/new', 1);
+             -- Unix 1700000100.5 and 1700000200
+             INSERT INTO history_visits VALUES (1, 1, 721692900.5, 'ENG-1 Fix sync');
+             INSERT INTO history_visits VALUES (2, 2, 721693000.0, 'Pay');
+             INSERT INTO history_visits VALUES (3, 1, 721693100.0, '');
+             INSERT INTO history_visits VALUES (4, 1, 600000000.0, 'Long ago');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let visits = get_browser_visits_range(
+            BrowserKind::Safari,
+            &profile.to_string_lossy(),
+            1_700_000_000,
+            1_700_001_000,
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&profile).ok();
+
+        assert_eq!(visits.len(), 3);
+        assert_eq!(visits[0].visit_date, 1_700_000_300_000_000);
+        assert_eq!(visits[0].title, None);
+        assert!(!BrowserFilter::default().includes(&visits[1]));
+        assert_eq!(visits[2].url, "https://linear.app/team/issue/ENG-1");
+        assert_eq!(visits[2].title.as_deref(), Some("ENG-1 Fix sync"));
+        assert_eq!(visits[2].visit_date, 1_700_000_100_500_000);
+        assert_eq!(visits[2].visit_count, 3);
+    }
+
+    #[test]
+    fn test_profiles_saved_without_flags_are_enabled() {
+        let profiles: Vec<BrowserProfile> =
+            serde_json::from_str(r#"[{"browser":"chrome","name":"Work","path":"/p/Default"}]"#)
+                .unwrap();
+        assert!(profiles[0].enabled);
+        assert_eq!(profiles[0].label, None);
+    }
+
+    #[test]
+    fn test_profile_at_recognises_history_files() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("ab12.work")).unwrap();
+        std::fs::write(dir.join("ab12.work/places.sqlite"), "").unwrap();
+        std::fs::create_dir_all(dir.join("Profile 2")).unwrap();
+        chromium_history(&dir.join("Profile 2/History"), &[]);
+
+        let zen = profile_at(&dir.join("ab12.work"));
+        let chromium = profile_at(&dir.join("Profile 2"));
+        let empty = profile_at(&dir);
+        std::fs::remove_dir_all(&dir).ok();
+
+        let zen = zen.unwrap();
+        assert_eq!((zen.browser, zen.name.as_str()), (BrowserKind::Zen, "work"));
+        assert_eq!(chromium.unwrap().browser, BrowserKind::Chrome);
+        assert!(empty.is_err());
+    }
+
+    #[test]
+    fn test_discover_profiles_with_names() {
+        let home = temp_dir();
+        let user_data = home.join("Library/Application Support/BraveSoftware/Brave-Browser");
+        for profile in ["Default", "Profile 1", "System Profile"] {
+            std::fs::create_dir_all(user_data.join(profile)).unwrap();
+        }
+        chromium_history(&user_data.join("Default/History"), &[]);
+        chromium_history(&user_data.join("Profile 1/History"), &[]);
+        std::fs::write(
+            user_data.join("Local State"),
+            r#"{"profile":{"info_cache":{"Default":{"name":"Work"}}}}"#,
+        )
+        .unwrap();
+
+        std::fs::create_dir_all(home.join("Library/Safari")).unwrap();
+        std::fs::write(home.join("Library/Safari/History.db"), "").unwrap();
+
+        let profiles = discover_browser_profiles(BrowserKind::Brave, &home);
+        let safari = discover_browser_profiles(BrowserKind::Safari, &home);
+        let found_nothing = discover_browser_profiles(BrowserKind::Edge, &home).is_empty();
+        std::fs::remove_dir_all(&home).ok();
+
+        let names: Vec<_> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Work", "Profile 1"]);
+        assert!(profiles.iter().all(|p| p.browser == BrowserKind::Brave));
+        assert!(found_nothing);
+        assert_eq!(safari.len(), 1);
+        assert_eq!(safari[0].name, "Default");
+        assert_eq!(
+            profile_dir_name(Path::new("/p/x1y2.Default (release)")),
+            "Default (release)"
+        );
+    }
+}
@@ -0,0 +1,647 @@
+use crate::calendar::CalendarEvent;
+use crate::db::{CalDavCalendar, CalDavChanges, CalDavResource, Database};
+use crate::ics;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use quick_xml::events::Event as XmlEvent;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Settings keys for the server and its credentials
+pub const URL_SETTING: &str = "caldav_url";
+pub const USERNAME_SETTING: &str = "caldav_username";
+pub const PASSWORD_SETTING: &str = "caldav_password";
+
+/// calendar-multiget requests are split so responses stay a manageable size
+const MULTIGET_BATCH: usize = 100;
+
+const PROPFIND_CALENDARS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <cs:getctag/>
+  </d:prop>
+</d:propfind>"#;
+
+const REPORT_EVENT_ETAGS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT"/>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#;
+
+#[derive(Debug, Clone)]
+pub struct CalDavConfig {
+    pub url: String, // A calendar, or a collection of calendars such as the calendar home
+    pub username: String,
+    pub password: String,
+}
+
+impl CalDavConfig {
+    /// None until a server URL is configured
+    pub fn load(db: &Database) -> rusqlite::Result<Option<Self>> {
+        let url = db.get_setting(URL_SETTING)?.unwrap_or_default();
+        if url.trim().is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(CalDavConfig {
+            url: url.trim().to_string(),
+            username: db
+                .get_setting(USERNAME_SETTING)?
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            password: db.get_setting(PASSWORD_SETTING)?.unwrap_or_default(),
+        }))
+    }
+}
+
+pub struct CalDavClient {
+    agent: ureq::Agent,
+    url: String,
+    authorization: Option<String>,
+}
+
+impl CalDavClient {
+    pub fn new(config: &CalDavConfig) -> Self {
+        let authorization = (!config.username.is_empty()).then(|| {
+            let credentials = format!("{}:{}", config.username, config.password);
+            format!("Basic {}", BASE64.encode(credentials))
+        });
+
+        CalDavClient {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+            url: config.url.clone(),
+            authorization,
+        }
+    }
+
+    /// Calendars in the configured collection, or the collection itself when it's a calendar
+    pub fn list_calendars(&self) -> Result<Vec<CalDavCalendar>, String> {
+        let responses = self.request("PROPFIND", &self.url, "1", PROPFIND_CALENDARS)?;
+        Ok(responses
+            .into_iter()
+            .filter(|response| response.resource_types.iter().any(|t| t == "calendar"))
+            .map(|response| CalDavCalendar {
+                display_name: response.prop("displayname"),
+                ctag: response.prop("getctag"),
+                href: response.href,
+            })
+            .collect())
+    }
+
+    /// Compare the server with the cached copy. Calendars with an unchanged ctag are skipped;
+    /// in the others only resources whose etag changed are downloaded.
+    pub fn fetch_changes(
+        &self,
+        cached_calendars: &[CalDavCalendar],
+        cached_resources: &[CalDavResource],
+    ) -> Result<CalDavChanges, String> {
+        let calendars = self.list_calendars()?;
+        let mut changes = CalDavChanges {
+            calendars: calendars.clone(),
+            ..Default::default()
+        };
+
+        for calendar in &calendars {
+            let unchanged = calendar.ctag.is_some()
+                && cached_calendars
+                    .iter()
+                    .any(|cached| cached.href == calendar.href && cached.ctag == calendar.ctag);
+            if unchanged {
+                continue;
+            }
+
+            let cached: HashMap<&str, Option<&str>> = cached_resources
+                .iter()
+                .filter(|resource| resource.calendar_href == calendar.href)
+                .map(|resource| (resource.href.as_str(), resource.etag.as_deref()))
+                .collect();
+            let remote: Vec<(String, Option<String>)> = self
+                .request("REPORT", &calendar.href, "1", REPORT_EVENT_ETAGS)?
+                .into_iter()
+                .map(|response| {
+                    let etag = response.prop("getetag");
+                    (response.href, etag)
+                })
+                .collect();
+
+            let (changed, removed) = diff_etags(&cached, &remote);
+            for batch in changed.chunks(MULTIGET_BATCH) {
+                changes
+                    .updated
+                    .extend(self.fetch_resources(&calendar.href, batch)?);
+            }
+            changes.removed.extend(removed);
+        }
+
+        Ok(changes)
+    }
+
+    fn fetch_resources(
+        &self,
+        calendar_href: &str,
+        hrefs: &[String],
+    ) -> Result<Vec<CalDavResource>, String> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+{}
+</c:calendar-multiget>"#,
+            hrefs
+                .iter()
+                .map(|href| format!("  <d:href>{}</d:href>", xml_escape(href)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+
+        Ok(self
+            .request("REPORT", calendar_href, "1", &body)?
+            .into_iter()
+            .filter_map(|response| {
+                Some(CalDavResource {
+                    data: response.prop("calendar-data")?,
+                    etag: response.prop("getetag"),
+                    calendar_href: calendar_href.to_string(),
+                    href: response.href,
+                })
+            })
+            .collect())
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        href: &str,
+        depth: &str,
+        body: &str,
+    ) -> Result<Vec<DavResponse>, String> {
+        let url = resolve_href(&self.url, href);
+        let mut request = self
+            .agent
+            .request(method, &url)
+            .set("Depth", depth)
+            .set("Content-Type", "application/xml; charset=utf-8");
+        if let Some(authorization) = &self.authorization {
+            request = request.set("Authorization", authorization);
+        }
+
+        let response = request.send_string(body).map_err(|e| match e {
+            ureq::Error::Status(401, _) => {
+                "The CalDAV server rejected the username or password".to_string()
+            }
+            ureq::Error::Status(code, _) => {
+                format!("CalDAV {} {} failed with status {}", method, url, code)
+            }
+            e => format!("CalDAV request to {} failed: {}", url, e),
+        })?;
+        let xml = response
+            .into_string()
+            .map_err(|e| format!("Failed to read CalDAV response: {}", e))?;
+        parse_multistatus(&xml)
+    }
+}
+
+/// Expand the cached resources into events overlapping the range, named after their calendar
+pub fn events_in_range(
+    config: &CalDavConfig,
+    calendars: &[CalDavCalendar],
+    resources: &[CalDavResource],
+    my_emails: &[String],
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<CalendarEvent>, String> {
+    let (start, end) = ics::parse_range(start_date, end_date)?;
+    let account = host(&config.url);
+    // Accounts are often named by email address, which is then how invitations address me
+    let mut my_emails = my_emails.to_vec();
+    if config.username.contains('@') {
+        my_emails.push(config.username.clone());
+    }
+
+    let mut events = Vec::new();
+    for resource in resources {
+        let calendar_name = calendars
+            .iter()
+            .find(|calendar| calendar.href == resource.calendar_href)
+            .and_then(|calendar| calendar.display_name.as_deref());
+        events.extend(
+            ics::parse_ics(&resource.data, calendar_name, &my_emails, start, end)
+                .into_iter()
+                .map(|event| CalendarEvent {
+                    calendar_account: account.clone(),
+                    ..event
+                }),
+        );
+    }
+    Ok(events)
+}
+
+/// Hrefs to download (new, or with a different or unknown etag) and cached hrefs that are no
+/// longer on the server
+fn diff_etags(
+    cached: &HashMap<&str, Option<&str>>,
+    remote: &[(String, Option<String>)],
+) -> (Vec<String>, Vec<String>) {
+    let changed = remote
+        .iter()
+        .filter(|(href, etag)| match (cached.get(href.as_str()), etag) {
+            (Some(Some(cached_etag)), Some(etag)) => cached_etag != etag,
+            _ => true,
+        })
+        .map(|(href, _)| href.clone())
+        .collect();
+
+    let mut removed: Vec<String> = cached
+        .keys()
+        .filter(|href| !remote.iter().any(|(remote_href, _)| remote_href == *href))
+        .map(|href| href.to_string())
+        .collect();
+    removed.sort();
+
+    (changed, removed)
+}
+
+/// Hrefs in responses are usually absolute paths on the same server
+fn resolve_href(base: &str, href: &str) -> String {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        return href.to_string();
+    }
+
+    let authority_start = base.find("://").map_or(0, |i| i + 3);
+    if href.starts_with('/') {
+        let origin_end = base[authority_start..]
+            .find('/')
+            .map_or(base.len(), |i| authority_start + i);
+        format!("{}{}", &base[..origin_end], href)
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), href)
+    }
+}
+
+/// Server host, used as the account name of CalDAV calendars
+fn host(url: &str) -> Option<String> {
+    let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = authority.split('/').next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// One `<response>` of a WebDAV multistatus, with the properties the server returned
+#[derive(Debug, Default, Clone, PartialEq)]
+struct DavResponse {
+    href: String,
+    props: HashMap<String, String>, // Keyed by local name, e.g. "getetag"
+    resource_types: Vec<String>,    // e.g. ["collection", "calendar"]
+}
+
+impl DavResponse {
+    fn prop(&self, name: &str) -> Option<String> {
+        self.props
+            .get(name)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Parse a multistatus body. Namespaces are ignored: the properties we read have distinct
+/// local names.
+fn parse_multistatus(xml: &str) -> Result<Vec<DavResponse>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut responses = Vec::new();
+    let mut response = DavResponse::default();
+    // Properties of the current propstat, kept only if its status is 200
+    let mut props: HashMap<String, String> = HashMap::new();
+    let mut resource_types: Vec<String> = Vec::new();
+    let mut status = String::new();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Invalid CalDAV response: {}", e))?;
+        match event {
+            XmlEvent::Start(e) => {
+                path.push(local_name(e.local_name().as_ref()));
+                text.clear();
+            }
+            XmlEvent::Empty(e) => {
+                let name = local_name(e.local_name().as_ref());
+                match path.last().map(String::as_str) {
+                    Some("resourcetype") => resource_types.push(name),
+                    Some("prop") => {
+                        props.insert(name, String::new());
+                    }
+                    _ => {}
+                }
+            }
+            XmlEvent::Text(e) => text.push_str(
+                &e.unescape()
+                    .map_err(|e| format!("Invalid CalDAV response: {}", e))?,
+            ),
+            XmlEvent::CData(e) => text.push_str(&String::from_utf8_lossy(&e.into_inner())),
+            XmlEvent::End(_) => {
+                let name = path.pop().unwrap_or_default();
+                match (name.as_str(), path.last().map(String::as_str)) {
+                    ("href", Some("response")) => response.href = text.trim().to_string(),
+                    ("status", Some("propstat")) => status = text.trim().to_string(),
+                    ("propstat", _) => {
+                        // Requested properties the server doesn't have come back as a 404 propstat
+                        if status.is_empty() || status.split_whitespace().nth(1) == Some("200") {
+                            response.props.extend(props.drain());
+                            response.resource_types.append(&mut resource_types);
+                        }
+                        props.clear();
+                        resource_types.clear();
+                        status.clear();
+                    }
+                    ("response", _) => responses.push(std::mem::take(&mut response)),
+                    (name, Some("resourcetype")) => resource_types.push(name.to_string()),
+                    (name, Some("prop")) => {
+                        props.insert(name.to_string(), text.clone());
+                    }
+                    _ => {}
+                }
+                text.clear();
+            }
+            XmlEvent::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(responses)
+}
+
+fn local_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multistatus() {
+        let xml = r#"<?xml version="1.0"?>
+<multistatus xmlns="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav" xmlns:CS="http://calendarserver.org/ns/">
+  <response>
+    <href>/alice/work/</href>
+    <propstat>
+      <prop>
+        <resourcetype><collection/><C:calendar/></resourcetype>
+        <displayname>Work &amp; Meetings</displayname>
+        <CS:getctag>"ctag-7"</CS:getctag>
+      </prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+  </response>
+  <response>
+    <href>/alice/work/standup.ics</href>
+    <propstat>
+      <prop>
+        <getetag>"e1"</getetag>
+        <C:calendar-data><![CDATA[BEGIN:VCALENDAR
+END:VCALENDAR]]></C:calendar-data>
+      </prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+    <propstat>
+      <prop><displayname/></prop>
+      <status>HTTP/1.1 404 Not Found</status>
+    </propstat>
+  </response>
+</multistatus>"#;
+
+        let responses = parse_multistatus(xml).unwrap();
+        assert_eq!(responses.len(), 2);
+
+        assert_eq!(responses[0].href, "/alice/work/");
+        assert_eq!(responses[0].resource_types, vec!["collection", "calendar"]);
+        assert_eq!(
+            responses[0].prop("displayname").as_deref(),
+            Some("Work & Meetings")
+        );
+        assert_eq!(responses[0].prop("getctag").as_deref(), Some("\"ctag-7\""));
+
+        assert_eq!(responses[1].prop("getetag").as_deref(), Some("\"e1\""));
+        assert_eq!(
+            responses[1].prop("calendar-data").as_deref(),
+            Some("BEGIN:VCALENDAR\nEND:VCALENDAR")
+        );
+        assert!(!responses[1].props.contains_key("displayname"));
+    }
+
+    /// Minimal CalDAV stand-in on a local port. Each request is answered by `respond` from its
+    /// method, path and body, and recorded as (method, path, body).
+    fn stub_server(
+        respond: fn(&str, &str, &str) -> String,
+    ) -> (String, std::sync::mpsc::Receiver<(String, String, String)>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cal/", listener.local_addr().unwrap());
+        let (sender, requests) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                let xml = respond(&method, &path, &body);
+                sender.send((method, path, body)).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 207 Multi-Status\r\nContent-Type: application/xml\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    xml.len(),
+                    xml
+                )
+                .unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn multistatus(responses: &[(&str, &str)]) -> String {
+        let responses: String = responses
+            .iter()
+            .map(|(href, props)| {
+                format!(
+                    "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop>\
+                     <d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+                    href, props
+                )
+            })
+            .collect();
+        format!(
+            r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:cs="http://calendarserver.org/ns/">{}</d:multistatus>"#,
+            responses
+        )
+    }
+
+    #[test]
+    fn test_fetch_changes_against_a_server() {
+        let (url, requests) = stub_server(|method, path, body| match (method, path) {
+            ("PROPFIND", "/cal/") => multistatus(&[
+                ("/cal/", "<d:resourcetype><d:collection/></d:resourcetype>"),
+                (
+                    "/cal/work/",
+                    "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>\
+                     <d:displayname>Work</d:displayname><cs:getctag>w1</cs:getctag>",
+                ),
+                (
+                    "/cal/home/",
+                    "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>\
+                     <d:displayname>Home</d:displayname><cs:getctag>h2</cs:getctag>",
+                ),
+            ]),
+            ("REPORT", "/cal/home/") if body.contains("calendar-multiget") => multistatus(&[(
+                "/cal/home/new.ics",
+                "<d:getetag>n1</d:getetag>\
+                     <c:calendar-data>BEGIN:VCALENDAR\nEND:VCALENDAR</c:calendar-data>",
+            )]),
+            ("REPORT", "/cal/home/") => multistatus(&[
+                ("/cal/home/same.ics", "<d:getetag>s1</d:getetag>"),
+                ("/cal/home/new.ics", "<d:getetag>n1</d:getetag>"),
+            ]),
+            _ => panic!("Unexpected {} {}", method, path),
+        });
+        let client = CalDavClient::new(&CalDavConfig {
+            url,
+            username: String::new(),
+            password: String::new(),
+        });
+        let calendar = |href: &str, ctag: &str| CalDavCalendar {
+            href: href.to_string(),
+            display_name: None,
+            ctag: Some(ctag.to_string()),
+        };
+        let resource = |href: &str, etag: &str| CalDavResource {
+            href: href.to_string(),
+            calendar_href: "/cal/home/".to_string(),
+            etag: Some(etag.to_string()),
+            data: String::new(),
+        };
+
+        // Work's ctag is unchanged; Home's moved on, with one resource added and one deleted
+        let changes = client
+            .fetch_changes(
+                &[calendar("/cal/work/", "w1"), calendar("/cal/home/", "h1")],
+                &[
+                    resource("/cal/home/same.ics", "s1"),
+                    resource("/cal/home/gone.ics", "g1"),
+                ],
+            )
+            .unwrap();
+
+        let names: Vec<Option<&str>> = changes
+            .calendars
+            .iter()
+            .map(|calendar| calendar.display_name.as_deref())
+            .collect();
+        assert_eq!(names, vec![Some("Work"), Some("Home")]);
+        assert_eq!(changes.updated.len(), 1);
+        assert_eq!(changes.updated[0].href, "/cal/home/new.ics");
+        assert_eq!(changes.updated[0].etag.as_deref(), Some("n1"));
+        assert_eq!(changes.updated[0].data, "BEGIN:VCALENDAR\nEND:VCALENDAR");
+        assert_eq!(changes.removed, vec!["/cal/home/gone.ics"]);
+
+        let requests: Vec<(String, String, String)> = requests.try_iter().collect();
+        let paths: Vec<(&str, &str)> = requests
+            .iter()
+            .map(|(method, path, _)| (method.as_str(), path.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("PROPFIND", "/cal/"),
+                ("REPORT", "/cal/home/"),
+                ("REPORT", "/cal/home/"),
+            ]
+        );
+        // Only the resource whose etag is new gets downloaded
+        let multiget = &requests[2].2;
+        assert!(multiget.contains("<d:href>/cal/home/new.ics</d:href>"));
+        assert!(!multiget.contains("same.ics"));
+    }
+
+    #[test]
+    fn test_diff_etags() {
+        let cached: HashMap<&str, Option<&str>> = [
+            ("/cal/same.ics", Some("1")),
+            ("/cal/edited.ics", Some("1")),
+            ("/cal/deleted.ics", Some("1")),
+        ]
+        .into_iter()
+        .collect();
+        let remote = vec![
+            ("/cal/same.ics".to_string(), Some("1".to_string())),
+            ("/cal/edited.ics".to_string(), Some("2".to_string())),
+            ("/cal/new.ics".to_string(), Some("1".to_string())),
+        ];
+
+        let (changed, removed) = diff_etags(&cached, &remote);
+        assert_eq!(changed, vec!["/cal/edited.ics", "/cal/new.ics"]);
+        assert_eq!(removed, vec!["/cal/deleted.ics"]);
+    }
+
+    #[test]
+    fn test_resolve_href_and_host() {
+        let base = "https://dav.example.com:5232/alice/";
+        assert_eq!(
+            resolve_href(base, "/alice/work/"),
+            "https://dav.example.com:5232/alice/work/"
+        );
+        assert_eq!(
+            resolve_href(base, "work/a.ics"),
+            "https://dav.example.com:5232/alice/work/a.ics"
+        );
+        assert_eq!(resolve_href(base, "http://other/x"), "http://other/x");
+        assert_eq!(
+            host("https://bob@dav.example.com:5232/alice/").as_deref(),
+            Some("dav.example.com:5232")
+        );
+    }
+}
@@ -12,6 +12,44 @@ pub struct CalendarEvent {
     pub attendees: Vec<String>,
     pub organizer: Option<String>,
     pub organizer_email: Option<String>,
+    pub calendar_name: Option<String>,
+    pub calendar_account: Option<String>,
+    pub status: EventStatus,
+    pub participant_status: Option<ParticipantStatus>, // None when I'm not an invitee
+    pub series_id: Option<String>, // Shared by every occurrence of a recurring event
+}
+
+impl CalendarEvent {
+    /// Cancelled by the organizer, or an invitation I turned down
+    pub fn is_cancelled_or_declined(&self) -> bool {
+        is_cancelled_or_declined(self.status, self.participant_status)
+    }
+}
+
+pub fn is_cancelled_or_declined(
+    status: EventStatus,
+    participant_status: Option<ParticipantStatus>,
+) -> bool {
+    status == EventStatus::Cancelled || participant_status == Some(ParticipantStatus::Declined)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EventStatus {
+    #[default]
+    Confirmed,
+    Tentative,
+    Cancelled,
+}
+
+/// My response to an invitation
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantStatus {
+    Pending,
+    Accepted,
+    Tentative,
+    Declined,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,7 +66,7 @@ pub async fn get_calendar_events_range(
     end_date: &str,
 ) -> Result<Vec<CalendarEvent>, String> {
     use chrono::DateTime;
-    use objc2_event_kit::{EKEntityType, EKEventStore};
+    use objc2_event_kit::{EKEntityType, EKEventStatus, EKEventStore, EKParticipantStatus};
 
     let granted = unsafe { request_calendar_access().await? };
 
@@ -81,15 +119,53 @@ pub async fn get_calendar_events_range(
             let notes = event.notes().map(|s| s.to_string());
 
             let mut attendees = Vec::new();
+            let mut participant_status = None;
             if let Some(attendees_array) = event.attendees() {
                 for j in 0..attendees_array.len() {
                     let attendee = &attendees_array[j];
                     if let Some(name) = attendee.name() {
                         attendees.push(name.to_string());
                     }
+                    if attendee.isCurrentUser() {
+                        participant_status = Some(match attendee.participantStatus() {
+                            EKParticipantStatus::Accepted => ParticipantStatus::Accepted,
+                            EKParticipantStatus::Tentative => ParticipantStatus::Tentative,
+                            // Delegating hands the meeting to someone else
+                            EKParticipantStatus::Declined | EKParticipantStatus::Delegated => {
+                                ParticipantStatus::Declined
+                            }
+                            _ => ParticipantStatus::Pending,
+                        });
+                    }
                 }
             }
 
+            let status = match event.status() {
+                EKEventStatus::Canceled => EventStatus::Cancelled,
+                // Exchange often leaves the status alone and only renames cancelled meetings
+                _ if title.starts_with("Canceled:") || title.starts_with("Cancelled:") => {
+                    EventStatus::Cancelled
+                }
+                EKEventStatus::Tentative => EventStatus::Tentative,
+                _ => EventStatus::Confirmed,
+            };
+
+            // Each occurrence has its own eventIdentifier; the external identifier is shared
+            let series_id = if event.hasRecurrenceRules() {
+                event
+                    .calendarItemExternalIdentifier()
+                    .map(|s| s.to_string())
+            } else {
+                None
+            };
+
+            let calendar = event.calendar();
+            let calendar_name = calendar.as_ref().map(|c| c.title().to_string());
+            let calendar_account = calendar
+                .as_ref()
+                .and_then(|c| c.source())
+                .map(|source| source.title().to_string());
+
             let (organizer, organizer_email) = if let Some(org) = event.organizer() {
                 let name = org.name().map(|n| n.to_string());
                 let email = org.URL().absoluteString().and_then(|url_string| {
@@ -112,6 +188,11 @@ pub async fn get_calendar_events_range(
                 attendees,
                 organizer,
                 organizer_email,
+                calendar_name,
+                calendar_account,
+                status,
+                participant_status,
+                series_id,
             });
         }
 
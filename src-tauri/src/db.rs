@@ -1,7 +1,14 @@
+use chrono::NaiveDate;
 use rusqlite::{Connection, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 
+use crate::browser::{self, BrowserKind, BrowserProfile};
+use crate::calendar::{EventStatus, ParticipantStatus};
+use crate::privacy::BrowserFilterRule;
+use crate::rules::{RuleCondition, RuleEngine};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Event {
     pub id: Option<i64>,
@@ -24,6 +31,12 @@ pub struct Event {
     pub organizer_id: Option<i64>, // FK to contacts table (calendar events)
     pub repository_path: Option<String>, // Canonical org/repo path (git/browser events)
     pub domain: Option<String>,    // Domain (browser_history events)
+    #[serde(default)]
+    pub series_id: Option<String>, // Recurring series shared by every occurrence (calendar events)
+    #[serde(default)]
+    pub assignment_source: AssignmentSource, // How project_id was decided
+    #[serde(default)]
+    pub assignment_rule_id: Option<i64>, // Rule that assigned the project (rule assignments only)
     #[serde(
         serialize_with = "serialize_timestamp",
         deserialize_with = "deserialize_timestamp"
@@ -36,6 +49,75 @@ pub struct Event {
     pub updated_at: i64, // Unix timestamp in seconds (UTC)
 }
 
+/// Provenance of an event's project assignment.
+/// Manual assignments are pinned: sync and rule application never overwrite them.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AssignmentSource {
+    #[default]
+    Unassigned,
+    Manual,
+    Rule,
+}
+
+impl AssignmentSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssignmentSource::Unassigned => "unassigned",
+            AssignmentSource::Manual => "manual",
+            AssignmentSource::Rule => "rule",
+        }
+    }
+
+    fn from_db(value: &str) -> Self {
+        match value {
+            "manual" => AssignmentSource::Manual,
+            "rule" => AssignmentSource::Rule,
+            _ => AssignmentSource::Unassigned,
+        }
+    }
+}
+
+/// Outcome of re-applying project rules to stored events
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RuleApplicationResult {
+    pub assigned_count: usize,
+    pub pinned_event_ids: Vec<i64>, // Matched by a rule but left alone due to a manual assignment
+}
+
+const EVENT_COLUMNS: &str = "id, event_type, title, start_date, end_date, external_id, external_link, type_specific_data, project_id, organizer_id, repository_path, domain, series_id, assignment_source, assignment_rule_id, created_at, updated_at";
+
+/// EVENT_COLUMNS qualified with a table alias, for queries that join other tables
+fn prefixed_event_columns(alias: &str) -> String {
+    EVENT_COLUMNS
+        .split(", ")
+        .map(|column| format!("{}.{}", alias, column))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn event_from_row(row: &rusqlite::Row) -> Result<Event> {
+    Ok(Event {
+        id: Some(row.get(0)?),
+        event_type: row.get(1)?,
+        title: row.get(2)?,
+        start_date: row.get(3)?,
+        end_date: row.get(4)?,
+        external_id: row.get(5)?,
+        external_link: row.get(6)?,
+        type_specific_data: row.get(7)?,
+        project_id: row.get(8)?,
+        organizer_id: row.get(9)?,
+        repository_path: row.get(10)?,
+        domain: row.get(11)?,
+        series_id: row.get(12)?,
+        assignment_source: AssignmentSource::from_db(&row.get::<_, String>(13)?),
+        assignment_rule_id: row.get(14)?,
+        created_at: row.get(15)?,
+        updated_at: row.get(16)?,
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Project {
     pub id: Option<i64>,
@@ -52,14 +134,86 @@ pub struct Project {
 pub struct ProjectRule {
     pub id: Option<i64>,
     pub project_id: i64,
-    pub rule_type: String, // "organizer", "title_pattern", "repository"
-    pub match_value: String,
-    pub priority: i64, // Lower number = higher priority (0 is highest)
+    pub rule_type: String, // "organizer", "title_pattern", "repository", ..., or "condition"
+    pub match_value: String, // JSON condition tree for "condition" rules
+    pub condition: Option<RuleCondition>, // None if the stored rule could not be interpreted
+    pub priority: i64,     // Lower number = higher priority (0 is highest)
+    #[serde(
+        serialize_with = "serialize_timestamp",
+        deserialize_with = "deserialize_timestamp"
+    )]
+    pub created_at: i64, // Unix timestamp in seconds (UTC)
+}
+
+/// Column that `aggregate_events` groups by
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateDimension {
+    Project,
+    EventType,
+    RepositoryPath,
+    Domain,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AggregateRow {
+    pub key: Option<String>, // Project name, event type, repository path or domain
+    pub project_id: Option<i64>, // Only set when grouping by project
+    pub event_count: i64,
+    pub duration_seconds: i64, // Sum of event durations; git and browser events are instants
+}
+
+/// Narrowing options for `search_events`; every field is optional
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SearchFilters {
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    pub project_id: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    pub start_date: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    pub end_date: Option<i64>,
+    pub repository_path: Option<String>,
+    pub domain: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchResult {
+    pub event: Event,
+    pub snippet: String, // Best matching fragment, with matches wrapped in ** **
+    pub rank: f64,       // bm25 score; lower is a better match
+}
+
+/// Event with its project and organizer names resolved, for exports
+#[derive(Debug, Serialize, Clone)]
+pub struct ExportEvent {
+    pub event: Event,
+    pub project_name: Option<String>,
+    pub organizer_name: Option<String>,
+}
+
+/// Time recorded against a project for one day. Entries are user data: sync never writes
+/// them and `clear_event_data` leaves them in place.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeEntry {
+    pub id: Option<i64>,
+    pub date: NaiveDate, // Local calendar day
+    pub project_id: i64,
+    pub duration_minutes: i64,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub event_ids: Vec<i64>, // Events that informed the entry, if still stored
     #[serde(
         serialize_with = "serialize_timestamp",
         deserialize_with = "deserialize_timestamp"
     )]
     pub created_at: i64, // Unix timestamp in seconds (UTC)
+    #[serde(
+        serialize_with = "serialize_timestamp",
+        deserialize_with = "deserialize_timestamp"
+    )]
+    pub updated_at: i64, // Unix timestamp in seconds (UTC)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -69,6 +223,12 @@ pub struct CalendarEventData {
     pub is_all_day: bool,
     pub organizer: Option<String>,
     pub attendees: Option<Vec<String>>,
+    pub calendar_name: Option<String>, // Title of the source calendar
+    pub calendar_account: Option<String>, // Account (EventKit source) holding the calendar
+    #[serde(default)]
+    pub status: EventStatus,
+    #[serde(default)]
+    pub participant_status: Option<ParticipantStatus>, // My response, if I was invited
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -82,6 +242,48 @@ pub struct GitEventData {
     pub origin_url: Option<String>,      // Full remote origin URL
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteEventData {
+    pub body: String, // Markdown
+}
+
+/// User-written log entry, stored as a "note" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteInput {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    pub start_date: i64,
+    pub duration_minutes: Option<i64>, // None for a point-in-time note
+    pub project_id: Option<i64>,
+}
+
+/// A calendar collection on the CalDAV server, with the ctag seen at the last sync
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CalDavCalendar {
+    pub href: String,
+    pub display_name: Option<String>,
+    pub ctag: Option<String>, // Changes whenever anything in the calendar changes
+}
+
+/// Cached iCalendar data of one CalDAV resource (an event and its overrides)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalDavResource {
+    pub href: String,
+    pub calendar_href: String,
+    pub etag: Option<String>,
+    pub data: String,
+}
+
+/// What changed on the CalDAV server since the cached copy
+#[derive(Debug, Default, Clone)]
+pub struct CalDavChanges {
+    pub calendars: Vec<CalDavCalendar>, // Every calendar currently on the server
+    pub updated: Vec<CalDavResource>,   // New or changed resources
+    pub removed: Vec<String>,           // Hrefs of resources deleted from the server
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WorkDomain {
     pub id: Option<i64>,
@@ -100,6 +302,8 @@ pub struct BrowserHistoryEventData {
     pub page_title: Option<String>,
     pub visit_count: i32,
     pub repository_path: Option<String>, // Canonical org/repo path if this is a code repo visit
+    #[serde(default)]
+    pub profile_label: Option<String>, // Label of the browser profile the visit came from
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -209,6 +413,9 @@ impl Database {
                 organizer_id INTEGER,
                 repository_path TEXT,
                 domain TEXT,
+                series_id TEXT,
+                assignment_source TEXT NOT NULL DEFAULT 'unassigned',
+                assignment_rule_id INTEGER,
                 created_at INTEGER NOT NULL,
                 updated_at INTEGER NOT NULL,
                 UNIQUE(event_type, external_id),
@@ -234,12 +441,63 @@ impl Database {
                 project_id INTEGER NOT NULL,
                 rule_type TEXT NOT NULL,
                 match_value TEXT NOT NULL,
+                condition TEXT,
                 priority INTEGER NOT NULL DEFAULT 0,
                 created_at INTEGER NOT NULL,
                 FOREIGN KEY (project_id) REFERENCES projects (id) ON DELETE CASCADE,
                 UNIQUE(rule_type, match_value)
             );
 
+            CREATE TABLE IF NOT EXISTS time_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL,
+                project_id INTEGER NOT NULL,
+                duration_minutes INTEGER NOT NULL,
+                note TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects (id) ON DELETE CASCADE
+            );
+
+            -- Events are referenced by their natural key so links survive clear_event_data and a resync
+            CREATE TABLE IF NOT EXISTS time_entry_sources (
+                time_entry_id INTEGER NOT NULL,
+                event_type TEXT NOT NULL,
+                external_id TEXT NOT NULL,
+                PRIMARY KEY (time_entry_id, event_type, external_id),
+                FOREIGN KEY (time_entry_id) REFERENCES time_entries (id) ON DELETE CASCADE
+            );
+
+            -- Local copy of CalDAV data so only resources with a new etag are downloaded
+            CREATE TABLE IF NOT EXISTS caldav_calendars (
+                href TEXT PRIMARY KEY,
+                display_name TEXT,
+                ctag TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS caldav_resources (
+                href TEXT PRIMARY KEY,
+                calendar_href TEXT NOT NULL,
+                etag TEXT,
+                data TEXT NOT NULL
+            );
+
+            -- How far each browser profile's history has been read, keyed by profile directory
+            CREATE TABLE IF NOT EXISTS browser_sync_cursors (
+                profile_path TEXT PRIMARY KEY,
+                synced_until INTEGER NOT NULL
+            );
+
+            -- User allow/deny rules for browser history, on top of the default patterns
+            CREATE TABLE IF NOT EXISTS browser_filter_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action TEXT NOT NULL,
+                field TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+
             CREATE TABLE IF NOT EXISTS work_domains (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 domain TEXT NOT NULL UNIQUE,
@@ -251,6 +509,7 @@ impl Database {
             CREATE INDEX IF NOT EXISTS idx_events_project_id ON events(project_id);
             CREATE INDEX IF NOT EXISTS idx_project_rules_project_id ON project_rules(project_id);
             CREATE INDEX IF NOT EXISTS idx_work_domains_domain ON work_domains(domain);
+            CREATE INDEX IF NOT EXISTS idx_time_entries_date ON time_entries(date);
             -- Performance indexes for browser events and common queries
             CREATE INDEX IF NOT EXISTS idx_events_type_date ON events(event_type, start_date DESC);
             CREATE INDEX IF NOT EXISTS idx_events_project_date ON events(project_id, start_date DESC) WHERE project_id IS NOT NULL;
@@ -262,12 +521,127 @@ impl Database {
             ",
         )?;
 
+        self.migrate_schema()?;
+
         // Initialize default settings if they don't exist
         self.init_default_settings()?;
 
         Ok(())
     }
 
+    /// Bring databases created by older versions up to the current schema
+    fn migrate_schema(&self) -> Result<()> {
+        if self.add_column_if_missing(
+            "events",
+            "assignment_source",
+            "TEXT NOT NULL DEFAULT 'unassigned'",
+        )? {
+            // Provenance wasn't tracked before, and sync used to clear project_id, so
+            // surviving assignments are treated as rule-derived rather than pinned.
+            self.conn.execute(
+                "UPDATE events SET assignment_source = 'rule' WHERE project_id IS NOT NULL",
+                [],
+            )?;
+        }
+        self.add_column_if_missing("events", "assignment_rule_id", "INTEGER")?;
+        self.add_column_if_missing("events", "series_id", "TEXT")?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_events_series ON events(series_id) WHERE series_id IS NOT NULL",
+            [],
+        )?;
+        self.ensure_search_index()?;
+
+        // Convert flat (rule_type, match_value) rules into condition trees
+        self.add_column_if_missing("project_rules", "condition", "TEXT")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, rule_type, match_value FROM project_rules WHERE condition IS NULL",
+        )?;
+        let legacy_rules = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        for (id, rule_type, match_value) in legacy_rules {
+            if let Ok(condition) = RuleCondition::from_rule(&rule_type, &match_value) {
+                self.conn.execute(
+                    "UPDATE project_rules SET condition = ?1 WHERE id = ?2",
+                    rusqlite::params![to_json(&condition)?, id],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the full-text index over events and keep it in sync with triggers.
+    /// The index is filled from existing rows the first time it's created.
+    fn ensure_search_index(&self) -> Result<()> {
+        let exists: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'events_fts'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        self.conn.execute_batch(&format!(
+            "
+            CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+                title, details, tokenize = 'unicode61 remove_diacritics 2'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS events_fts_insert AFTER INSERT ON events BEGIN
+                INSERT INTO events_fts (rowid, title, details) VALUES (new.id, new.title, {new});
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS events_fts_delete AFTER DELETE ON events BEGIN
+                DELETE FROM events_fts WHERE rowid = old.id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS events_fts_update AFTER UPDATE OF title, type_specific_data ON events BEGIN
+                DELETE FROM events_fts WHERE rowid = old.id;
+                INSERT INTO events_fts (rowid, title, details) VALUES (new.id, new.title, {new});
+            END;
+            ",
+            new = search_details_sql("new")
+        ))?;
+
+        if !exists {
+            self.conn.execute(
+                &format!(
+                    "INSERT INTO events_fts (rowid, title, details) SELECT id, title, {} FROM events",
+                    search_details_sql("events")
+                ),
+                [],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the column was added
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<bool> {
+        let exists: bool = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1",
+                table
+            ),
+            [column],
+            |row| row.get(0),
+        )?;
+
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
+
+        Ok(!exists)
+    }
+
     fn init_default_settings(&self) -> Result<()> {
         let now = chrono::Utc::now().timestamp();
 
@@ -321,59 +695,51 @@ impl Database {
             }
         }
 
-        // Auto-detect Zen profile path if not already set
-        let has_zen_profile: bool = self.conn.query_row(
-            "SELECT COUNT(*) FROM settings WHERE key = 'zen_browser_profile_path'",
-            [],
-            |row| row.get::<_, i64>(0).map(|count| count > 0),
-        )?;
-
-        if !has_zen_profile {
-            if let Ok(home) = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")) {
-                let profiles_dir = format!("{}/Library/Application Support/zen/Profiles", home);
-                if let Ok(entries) = std::fs::read_dir(&profiles_dir) {
-                    // Collect all profiles that contain "default"
-                    let mut default_profiles = Vec::new();
-                    for entry in entries.flatten() {
-                        if let Some(name) = entry.file_name().to_str() {
-                            let name_lower = name.to_lowercase();
-                            if name_lower.contains("default") {
-                                default_profiles.push((name.to_string(), entry.path()));
-                            }
-                        }
-                    }
-
-                    // Prefer profiles with "release" in the name, then fall back to any default
-                    let selected_profile = default_profiles
-                        .iter()
-                        .find(|(name, _)| name.to_lowercase().contains("release"))
-                        .or_else(|| default_profiles.first());
-
-                    if let Some((_, path)) = selected_profile {
-                        let profile_path = path.to_string_lossy().to_string();
-                        self.conn.execute(
-                            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)",
-                            rusqlite::params!["zen_browser_profile_path", profile_path, now],
-                        )?;
-                    }
-                }
-            }
+        // Start the browser profile list from the single Zen profile older versions stored,
+        // or from the auto-detected Zen profile
+        if self
+            .get_setting(browser::BROWSER_PROFILES_SETTING)?
+            .is_none()
+        {
+            let zen_path = self
+                .get_setting("zen_browser_profile_path")?
+                .filter(|path| !path.trim().is_empty())
+                .or_else(|| browser::auto_detect_zen_profile().ok().flatten());
+            let profiles: Vec<BrowserProfile> = zen_path
+                .map(|path| BrowserProfile::new(BrowserKind::Zen, std::path::Path::new(&path)))
+                .into_iter()
+                .collect();
+            browser::save_profiles(self, &profiles)?;
+            self.conn.execute(
+                "DELETE FROM settings WHERE key = 'zen_browser_profile_path'",
+                [],
+            )?;
         }
 
         Ok(())
     }
 
+    /// Remove synced data so it can be rebuilt. Projects, rules, settings, time entries and
+    /// notes stay, since none of them can be recovered by syncing.
     pub fn clear_event_data(&self) -> Result<()> {
         self.conn.execute_batch(
             "
-            DELETE FROM events;
+            DELETE FROM events WHERE event_type != 'note';
             DELETE FROM contacts;
             DELETE FROM sync_metadata;
+            DELETE FROM browser_sync_cursors;
+            DELETE FROM caldav_resources;
+            DELETE FROM caldav_calendars;
             ",
         )?;
         Ok(())
     }
 
+    /// Group several operations so they commit (or roll back) together
+    pub fn transaction(&self) -> Result<rusqlite::Transaction<'_>> {
+        self.conn.unchecked_transaction()
+    }
+
     pub fn upsert_event(&self, event: &Event) -> Result<(i64, bool)> {
         let now = chrono::Utc::now().timestamp();
         let created_at = if event.created_at == 0 {
@@ -392,19 +758,27 @@ impl Database {
             )
             .unwrap_or(false);
 
+        // An incoming assignment replaces rule-derived or empty assignments, but never a manual
+        // one. Incoming rows without an assignment (plain syncs) keep whatever is stored.
         self.conn.execute(
-            "INSERT INTO events (event_type, title, start_date, end_date, external_id, external_link, type_specific_data, project_id, organizer_id, repository_path, domain, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            "INSERT INTO events (event_type, title, start_date, end_date, external_id, external_link, type_specific_data, project_id, organizer_id, repository_path, domain, series_id, assignment_source, assignment_rule_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
              ON CONFLICT(event_type, external_id) DO UPDATE SET
                 title = excluded.title,
                 start_date = excluded.start_date,
                 end_date = excluded.end_date,
                 external_link = excluded.external_link,
                 type_specific_data = excluded.type_specific_data,
-                project_id = excluded.project_id,
+                project_id = CASE WHEN events.assignment_source = 'manual' OR excluded.assignment_source = 'unassigned'
+                    THEN events.project_id ELSE excluded.project_id END,
+                assignment_rule_id = CASE WHEN events.assignment_source = 'manual' OR excluded.assignment_source = 'unassigned'
+                    THEN events.assignment_rule_id ELSE excluded.assignment_rule_id END,
+                assignment_source = CASE WHEN events.assignment_source = 'manual' OR excluded.assignment_source = 'unassigned'
+                    THEN events.assignment_source ELSE excluded.assignment_source END,
                 organizer_id = excluded.organizer_id,
                 repository_path = excluded.repository_path,
                 domain = excluded.domain,
+                series_id = excluded.series_id,
                 updated_at = excluded.updated_at",
             rusqlite::params![
                 event.event_type,
@@ -418,6 +792,9 @@ impl Database {
                 event.organizer_id,
                 event.repository_path,
                 event.domain,
+                event.series_id,
+                event.assignment_source.as_str(),
+                event.assignment_rule_id,
                 created_at,
                 now,
             ],
@@ -434,10 +811,114 @@ impl Database {
         Ok((event_id, !exists))
     }
 
+    // Note operations
+    pub fn create_note(&self, note: &NoteInput) -> Result<i64> {
+        let mut event = note_event(note)?;
+        // Notes have no upstream id, so generate one that can never collide with another note
+        event.external_id = Some(format!("note-{}", uuid::Uuid::new_v4()));
+        let (id, _) = self.upsert_event(&event)?;
+        Ok(id)
+    }
+
+    pub fn update_note(&self, id: i64, note: &NoteInput) -> Result<()> {
+        let event = note_event(note)?;
+        let now = chrono::Utc::now().timestamp();
+        let updated = self.conn.execute(
+            "UPDATE events
+             SET title = ?1, start_date = ?2, end_date = ?3, type_specific_data = ?4,
+                 project_id = ?5, assignment_source = ?6, assignment_rule_id = NULL, updated_at = ?7
+             WHERE id = ?8 AND event_type = 'note'",
+            rusqlite::params![
+                event.title,
+                event.start_date,
+                event.end_date,
+                event.type_specific_data,
+                event.project_id,
+                event.assignment_source.as_str(),
+                now,
+                id
+            ],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    }
+
+    pub fn delete_note(&self, id: i64) -> Result<()> {
+        let deleted = self.conn.execute(
+            "DELETE FROM events WHERE id = ?1 AND event_type = 'note'",
+            [id],
+        )?;
+        if deleted == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    }
+
+    /// Delete a synced event by its natural key. Returns true if there was one.
+    pub fn delete_event_by_external_id(&self, event_type: &str, external_id: &str) -> Result<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM events WHERE event_type = ?1 AND external_id = ?2",
+            [event_type, external_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Whether a stored event is manually assigned, which sync must not remove
+    pub fn is_event_pinned(&self, event_type: &str, external_id: &str) -> Result<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM events
+             WHERE event_type = ?1 AND external_id = ?2 AND assignment_source = 'manual')",
+            [event_type, external_id],
+            |row| row.get(0),
+        )
+    }
+
+    /// Delete calendar events overlapping [start, end) whose external id isn't in `current`,
+    /// i.e. ones deleted at the source or moved out of the window. Returns how many went.
+    /// Manually assigned events are kept; clearing their project lets the next sync remove them.
+    pub fn delete_missing_calendar_events(
+        &self,
+        start: i64,
+        end: i64,
+        current: &HashSet<String>,
+    ) -> Result<usize> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, external_id FROM events
+             WHERE event_type = 'calendar' AND start_date < ?2
+               AND (end_date > ?1 OR start_date >= ?1)
+               AND assignment_source != 'manual'",
+        )?;
+        let missing: Vec<i64> = stmt
+            .query_map([start, end], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+            })?
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|(_, external_id)| !external_id.as_ref().is_some_and(|id| current.contains(id)))
+            .map(|(id, _)| id)
+            .collect();
+
+        let tx = self.conn.unchecked_transaction()?;
+        for id in &missing {
+            tx.execute("DELETE FROM events WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+        Ok(missing.len())
+    }
+
+    /// Manually assign an event, pinning it against sync and rules.
+    /// Clearing the project removes the pin so rules can claim the event again.
     pub fn assign_event_to_project(&self, event_id: i64, project_id: Option<i64>) -> Result<()> {
+        let source = if project_id.is_some() {
+            AssignmentSource::Manual
+        } else {
+            AssignmentSource::Unassigned
+        };
         self.conn.execute(
-            "UPDATE events SET project_id = ?1 WHERE id = ?2",
-            rusqlite::params![project_id, event_id],
+            "UPDATE events SET project_id = ?1, assignment_source = ?2, assignment_rule_id = NULL WHERE id = ?3",
+            rusqlite::params![project_id, source.as_str(), event_id],
         )?;
         Ok(())
     }
@@ -446,7 +927,7 @@ impl Database {
         // Get work domains once for the SQL filter
         let work_domains = self.get_work_domains()?;
 
-        let mut sql = "SELECT id, event_type, title, start_date, end_date, external_id, external_link, type_specific_data, project_id, organizer_id, repository_path, domain, created_at, updated_at FROM events".to_string();
+        let mut sql = format!("SELECT {} FROM events", EVENT_COLUMNS);
 
         let mut conditions = Vec::new();
         let mut owned_conditions: Vec<String> = Vec::new(); // Store owned strings
@@ -513,30 +994,56 @@ impl Database {
 
         let params_refs: Vec<&dyn rusqlite::ToSql> =
             params_vec.iter().map(|b| b.as_ref()).collect();
-        let event_iter = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(Event {
-                id: Some(row.get(0)?),
-                event_type: row.get(1)?,
-                title: row.get(2)?,
-                start_date: row.get(3)?,
-                end_date: row.get(4)?,
-                external_id: row.get(5)?,
-                external_link: row.get(6)?,
-                type_specific_data: row.get(7)?,
-                project_id: row.get(8)?,
-                organizer_id: row.get(9)?,
-                repository_path: row.get(10)?,
-                domain: row.get(11)?,
-                created_at: row.get(12)?,
-                updated_at: row.get(13)?,
-            })
-        })?;
+        let event_iter = stmt.query_map(params_refs.as_slice(), event_from_row)?;
 
         let events: Vec<Event> = event_iter.collect::<Result<Vec<_>>>()?;
 
         Ok(events)
     }
 
+    /// Events overlapping [start, end), oldest first, including ones that began earlier and
+    /// run into the range. Browser events are limited to work domains like in `get_events`.
+    pub fn get_events_overlapping(&self, start: i64, end: i64) -> Result<Vec<Event>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM events
+             WHERE start_date < ?2 AND (end_date > ?1 OR start_date >= ?1)
+             AND (event_type != 'browser_history'
+                  OR domain IN (SELECT domain FROM work_domains))
+             ORDER BY start_date ASC",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt
+            .query_map([start, end], event_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    /// Every stored event, without work domain filtering, as seen by project rules
+    pub fn get_all_events(&self) -> Result<Vec<Event>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM events ORDER BY start_date ASC",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt
+            .query_map([], event_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    pub fn get_event(&self, event_id: i64) -> Result<Option<Event>> {
+        let result = self.conn.query_row(
+            &format!("SELECT {} FROM events WHERE id = ?1", EVENT_COLUMNS),
+            [event_id],
+            event_from_row,
+        );
+
+        match result {
+            Ok(event) => Ok(Some(event)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn get_event_project(&self, event_id: i64) -> Result<Option<Project>> {
         let result = self.conn.query_row(
             "SELECT p.id, p.name, p.color, p.created_at
@@ -640,6 +1147,28 @@ impl Database {
         Ok(())
     }
 
+    /// When the profile's history was last read up to, or None if it never has been
+    pub fn get_browser_sync_cursor(&self, profile_path: &str) -> Result<Option<i64>> {
+        match self.conn.query_row(
+            "SELECT synced_until FROM browser_sync_cursors WHERE profile_path = ?1",
+            [profile_path],
+            |row| row.get(0),
+        ) {
+            Ok(synced_until) => Ok(Some(synced_until)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn set_browser_sync_cursor(&self, profile_path: &str, synced_until: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO browser_sync_cursors (profile_path, synced_until) VALUES (?1, ?2)
+             ON CONFLICT(profile_path) DO UPDATE SET synced_until = excluded.synced_until",
+            rusqlite::params![profile_path, synced_until],
+        )?;
+        Ok(())
+    }
+
     pub fn create_project(&self, name: &str, color: Option<&str>) -> Result<i64> {
         let now = chrono::Utc::now().timestamp();
 
@@ -676,11 +1205,7 @@ impl Database {
     ) -> Result<Vec<Event>> {
         // Note: For project-specific queries, we can skip work domain filtering
         // since browser events assigned to projects are already considered "work"
-        let mut query = String::from(
-            "SELECT id, event_type, title, start_date, end_date, external_id, external_link, type_specific_data, project_id, organizer_id, repository_path, domain, created_at, updated_at
-             FROM events
-             WHERE project_id = ?"
-        );
+        let mut query = format!("SELECT {} FROM events WHERE project_id = ?", EVENT_COLUMNS);
 
         let mut param_count = 1;
 
@@ -707,29 +1232,26 @@ impl Database {
         }
 
         let events: Vec<Event> = stmt
-            .query_map(rusqlite::params_from_iter(params_vec), |row| {
-                Ok(Event {
-                    id: Some(row.get(0)?),
-                    event_type: row.get(1)?,
-                    title: row.get(2)?,
-                    start_date: row.get(3)?,
-                    end_date: row.get(4)?,
-                    external_id: row.get(5)?,
-                    external_link: row.get(6)?,
-                    type_specific_data: row.get(7)?,
-                    project_id: row.get(8)?,
-                    organizer_id: row.get(9)?,
-                    repository_path: row.get(10)?,
-                    domain: row.get(11)?,
-                    created_at: row.get(12)?,
-                    updated_at: row.get(13)?,
-                })
-            })?
+            .query_map(rusqlite::params_from_iter(params_vec), event_from_row)?
             .collect::<Result<Vec<_>>>()?;
 
         Ok(events)
     }
 
+    /// Occurrences of recurring events starting in [start, end), oldest first
+    pub fn get_series_events(&self, start: i64, end: i64) -> Result<Vec<Event>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM events
+             WHERE series_id IS NOT NULL AND start_date >= ?1 AND start_date < ?2
+             ORDER BY start_date ASC, id ASC",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt
+            .query_map([start, end], event_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
     pub fn get_project(&self, id: i64) -> Result<Option<Project>> {
         let result = self.conn.query_row(
             "SELECT id, name, color, created_at FROM projects WHERE id = ?1",
@@ -751,28 +1273,403 @@ impl Database {
         }
     }
 
-    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT value FROM settings WHERE key = ?1")?;
-        let mut rows = stmt.query([key])?;
+    pub fn get_project_by_name(&self, name: &str) -> Result<Option<Project>> {
+        let result = self.conn.query_row(
+            "SELECT id, name, color, created_at FROM projects WHERE name = ?1",
+            rusqlite::params![name],
+            |row| {
+                Ok(Project {
+                    id: Some(row.get(0)?),
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            },
+        );
 
-        if let Some(row) = rows.next()? {
-            let value: String = row.get(0)?;
-            Ok(Some(value))
-        } else {
-            Ok(None)
+        match result {
+            Ok(project) => Ok(Some(project)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
-    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
-        let now = chrono::Utc::now().timestamp();
-        self.conn.execute(
-            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
-             ON CONFLICT(key) DO UPDATE SET value = ?2, updated_at = ?3",
-            (key, value, now),
-        )?;
-        Ok(())
+    /// Event counts and durations grouped by `dimension`, for events starting in [start, end).
+    /// Browser events are limited to work domains, the same way `get_events` does. All-day,
+    /// cancelled and declined meetings are left out like in the estimate and series totals.
+    pub fn aggregate_events(
+        &self,
+        dimension: AggregateDimension,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<AggregateRow>> {
+        let (select, group_by) = match dimension {
+            AggregateDimension::Project => ("e.project_id, p.name", "e.project_id"),
+            AggregateDimension::EventType => ("NULL, e.event_type", "e.event_type"),
+            AggregateDimension::RepositoryPath => ("NULL, e.repository_path", "e.repository_path"),
+            AggregateDimension::Domain => ("NULL, e.domain", "e.domain"),
+        };
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, COUNT(*), COALESCE(SUM(MAX(e.end_date - e.start_date, 0)), 0)
+             FROM events e
+             LEFT JOIN projects p ON p.id = e.project_id
+             WHERE e.start_date >= ?1 AND e.start_date < ?2
+             AND {}
+             AND NOT {}
+             AND NOT (e.event_type = 'calendar'
+                  AND json_extract(e.type_specific_data, '$.is_all_day') IS 1)
+             GROUP BY {}
+             ORDER BY COUNT(*) DESC",
+            select,
+            work_domain_sql("e"),
+            skipped_meeting_sql("e"),
+            group_by
+        ))?;
+
+        let rows = stmt
+            .query_map([start, end], |row| {
+                Ok(AggregateRow {
+                    project_id: row.get(0)?,
+                    key: row.get(1)?,
+                    event_count: row.get(2)?,
+                    duration_seconds: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(rows)
+    }
+
+    /// Full-text search over titles, calendar notes and locations, page URLs and titles,
+    /// commit messages and note bodies. Results are ranked best first.
+    pub fn search_events(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchResult>> {
+        let Some(match_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let columns = prefixed_event_columns("e");
+        let mut sql = format!(
+            "SELECT {}, snippet(events_fts, -1, '**', '**', '…', 12), bm25(events_fts, 5.0, 1.0) AS rank
+             FROM events_fts
+             JOIN events e ON e.id = events_fts.rowid
+             WHERE events_fts MATCH ?1
+             AND {}",
+            columns,
+            work_domain_sql("e")
+        );
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(match_query)];
+        let mut push = |condition: &str, value: Box<dyn rusqlite::ToSql>| {
+            params.push(value);
+            sql.push_str(&format!(
+                " AND {}",
+                condition.replace('?', &format!("?{}", params.len()))
+            ));
+        };
+
+        if !filters.event_types.is_empty() {
+            push(
+                "e.event_type IN (SELECT value FROM json_each(?))",
+                Box::new(to_json(&filters.event_types)?),
+            );
+        }
+        if let Some(project_id) = filters.project_id {
+            push("e.project_id = ?", Box::new(project_id));
+        }
+        if let Some(start_date) = filters.start_date {
+            push("e.start_date >= ?", Box::new(start_date));
+        }
+        if let Some(end_date) = filters.end_date {
+            push("e.start_date < ?", Box::new(end_date));
+        }
+        if let Some(repository_path) = &filters.repository_path {
+            push("e.repository_path = ?", Box::new(repository_path.clone()));
+        }
+        if let Some(domain) = &filters.domain {
+            push("e.domain = ?", Box::new(domain.clone()));
+        }
+
+        params.push(Box::new(filters.limit.unwrap_or(100)));
+        sql.push_str(&format!(" ORDER BY rank LIMIT ?{}", params.len()));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let results = stmt
+            .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                Ok(SearchResult {
+                    event: event_from_row(row)?,
+                    snippet: row.get(17)?,
+                    rank: row.get(18)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(results)
+    }
+
+    /// Events starting in [start, end), oldest first, with project and organizer names.
+    /// Browser events are limited to work domains, the same way `get_events` does, and
+    /// cancelled or declined meetings are left out like in the period totals.
+    pub fn get_export_events(&self, start: i64, end: i64) -> Result<Vec<ExportEvent>> {
+        let columns = prefixed_event_columns("e");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, p.name, c.name
+             FROM events e
+             LEFT JOIN projects p ON p.id = e.project_id
+             LEFT JOIN contacts c ON c.id = e.organizer_id
+             WHERE e.start_date >= ?1 AND e.start_date < ?2
+             AND {}
+             AND NOT {}
+             ORDER BY e.start_date ASC, e.id ASC",
+            columns,
+            work_domain_sql("e"),
+            skipped_meeting_sql("e")
+        ))?;
+
+        let events = stmt
+            .query_map([start, end], |row| {
+                Ok(ExportEvent {
+                    event: event_from_row(row)?,
+                    project_name: row.get(17)?,
+                    organizer_name: row.get(18)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(events)
+    }
+
+    // Time entry operations
+    pub fn create_time_entry(
+        &self,
+        date: NaiveDate,
+        project_id: i64,
+        duration_minutes: i64,
+        note: Option<&str>,
+        event_ids: &[i64],
+    ) -> Result<i64> {
+        validate_duration(duration_minutes)?;
+        let now = chrono::Utc::now().timestamp();
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO time_entries (date, project_id, duration_minutes, note, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            rusqlite::params![date.to_string(), project_id, duration_minutes, note, now],
+        )?;
+        let id = tx.last_insert_rowid();
+        self.set_time_entry_sources(id, event_ids)?;
+        tx.commit()?;
+
+        Ok(id)
+    }
+
+    pub fn update_time_entry(
+        &self,
+        id: i64,
+        date: NaiveDate,
+        project_id: i64,
+        duration_minutes: i64,
+        note: Option<&str>,
+        event_ids: &[i64],
+    ) -> Result<()> {
+        validate_duration(duration_minutes)?;
+        let now = chrono::Utc::now().timestamp();
+
+        let tx = self.conn.unchecked_transaction()?;
+        let updated = tx.execute(
+            "UPDATE time_entries
+             SET date = ?1, project_id = ?2, duration_minutes = ?3, note = ?4, updated_at = ?5
+             WHERE id = ?6",
+            rusqlite::params![
+                date.to_string(),
+                project_id,
+                duration_minutes,
+                note,
+                now,
+                id
+            ],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        self.set_time_entry_sources(id, event_ids)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn delete_time_entry(&self, id: i64) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM time_entry_sources WHERE time_entry_id = ?1",
+            [id],
+        )?;
+        tx.execute("DELETE FROM time_entries WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Entries dated between `start` and `end` (inclusive), oldest first
+    pub fn get_time_entries(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<TimeEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, date, project_id, duration_minutes, note, created_at, updated_at
+             FROM time_entries
+             WHERE date >= ?1 AND date <= ?2
+             ORDER BY date ASC, id ASC",
+        )?;
+        let mut entries = stmt
+            .query_map(
+                rusqlite::params![start.to_string(), end.to_string()],
+                |row| {
+                    let date: String = row.get(1)?;
+                    Ok(TimeEntry {
+                        id: Some(row.get(0)?),
+                        date: date.parse().map_err(|e| {
+                            rusqlite::Error::FromSqlConversionFailure(
+                                1,
+                                rusqlite::types::Type::Text,
+                                Box::new(e),
+                            )
+                        })?,
+                        project_id: row.get(2)?,
+                        duration_minutes: row.get(3)?,
+                        note: row.get(4)?,
+                        event_ids: Vec::new(),
+                        created_at: row.get(5)?,
+                        updated_at: row.get(6)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut sources = self.conn.prepare(
+            "SELECT e.id FROM time_entry_sources s
+             JOIN events e ON e.event_type = s.event_type AND e.external_id = s.external_id
+             WHERE s.time_entry_id = ?1
+             ORDER BY e.start_date ASC",
+        )?;
+        for entry in &mut entries {
+            entry.event_ids = sources
+                .query_map([entry.id], |row| row.get(0))?
+                .collect::<Result<Vec<_>>>()?;
+        }
+
+        Ok(entries)
+    }
+
+    fn set_time_entry_sources(&self, time_entry_id: i64, event_ids: &[i64]) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM time_entry_sources WHERE time_entry_id = ?1",
+            [time_entry_id],
+        )?;
+        for event_id in event_ids {
+            let linked = self.conn.execute(
+                "INSERT OR IGNORE INTO time_entry_sources (time_entry_id, event_type, external_id)
+                 SELECT ?1, event_type, external_id FROM events
+                 WHERE id = ?2 AND external_id IS NOT NULL",
+                rusqlite::params![time_entry_id, event_id],
+            )?;
+            if linked == 0 && self.get_event(*event_id)?.is_none() {
+                return Err(rusqlite::Error::InvalidParameterName(format!(
+                    "Event {} does not exist",
+                    event_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT value FROM settings WHERE key = ?1")?;
+        let mut rows = stmt.query([key])?;
+
+        if let Some(row) = rows.next()? {
+            let value: String = row.get(0)?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = ?2, updated_at = ?3",
+            (key, value, now),
+        )?;
+        Ok(())
+    }
+
+    // CalDAV cache operations
+    pub fn get_caldav_calendars(&self) -> Result<Vec<CalDavCalendar>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT href, display_name, ctag FROM caldav_calendars ORDER BY href")?;
+        let calendars = stmt
+            .query_map([], |row| {
+                Ok(CalDavCalendar {
+                    href: row.get(0)?,
+                    display_name: row.get(1)?,
+                    ctag: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(calendars)
+    }
+
+    pub fn get_caldav_resources(&self) -> Result<Vec<CalDavResource>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT href, calendar_href, etag, data FROM caldav_resources ORDER BY href",
+        )?;
+        let resources = stmt
+            .query_map([], |row| {
+                Ok(CalDavResource {
+                    href: row.get(0)?,
+                    calendar_href: row.get(1)?,
+                    etag: row.get(2)?,
+                    data: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(resources)
+    }
+
+    /// Store the server's calendar list and changed resources in one transaction. Resources
+    /// of calendars that are no longer on the server are dropped.
+    pub fn apply_caldav_changes(&self, changes: &CalDavChanges) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM caldav_calendars", [])?;
+        for calendar in &changes.calendars {
+            tx.execute(
+                "INSERT INTO caldav_calendars (href, display_name, ctag) VALUES (?1, ?2, ?3)",
+                rusqlite::params![calendar.href, calendar.display_name, calendar.ctag],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM caldav_resources
+             WHERE calendar_href NOT IN (SELECT href FROM caldav_calendars)",
+            [],
+        )?;
+        for href in &changes.removed {
+            tx.execute("DELETE FROM caldav_resources WHERE href = ?1", [href])?;
+        }
+        for resource in &changes.updated {
+            tx.execute(
+                "INSERT INTO caldav_resources (href, calendar_href, etag, data)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(href) DO UPDATE SET calendar_href = ?2, etag = ?3, data = ?4",
+                rusqlite::params![
+                    resource.href,
+                    resource.calendar_href,
+                    resource.etag,
+                    resource.data
+                ],
+            )?;
+        }
+        tx.commit()
     }
 
     // Work domain operations
@@ -794,6 +1691,66 @@ impl Database {
         Ok(domains)
     }
 
+    // Browser filter rule operations
+    pub fn get_browser_filter_rules(&self) -> Result<Vec<BrowserFilterRule>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, action, field, kind, pattern FROM browser_filter_rules ORDER BY id",
+        )?;
+
+        let rules = stmt
+            .query_map([], |row| {
+                Ok(BrowserFilterRule {
+                    id: Some(row.get(0)?),
+                    action: enum_from_db(row, 1)?,
+                    field: enum_from_db(row, 2)?,
+                    kind: enum_from_db(row, 3)?,
+                    pattern: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(rules)
+    }
+
+    pub fn create_browser_filter_rule(&self, rule: &BrowserFilterRule) -> Result<i64> {
+        rule.validate()
+            .map_err(rusqlite::Error::InvalidParameterName)?;
+        let now = chrono::Utc::now().timestamp();
+        self.conn.execute(
+            "INSERT INTO browser_filter_rules (action, field, kind, pattern, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                enum_to_db(&rule.action)?,
+                enum_to_db(&rule.field)?,
+                enum_to_db(&rule.kind)?,
+                rule.pattern.trim(),
+                now
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_browser_filter_rule(&self, id: i64, rule: &BrowserFilterRule) -> Result<()> {
+        rule.validate()
+            .map_err(rusqlite::Error::InvalidParameterName)?;
+        self.conn.execute(
+            "UPDATE browser_filter_rules SET action = ?1, field = ?2, kind = ?3, pattern = ?4 WHERE id = ?5",
+            rusqlite::params![
+                enum_to_db(&rule.action)?,
+                enum_to_db(&rule.field)?,
+                enum_to_db(&rule.kind)?,
+                rule.pattern.trim(),
+                id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_browser_filter_rule(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM browser_filter_rules WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
     // GitHub org operations (stored in settings as JSON array)
     pub fn get_github_orgs(&self) -> Result<Vec<String>> {
         match self.get_setting("github_orgs")? {
@@ -878,7 +1835,19 @@ impl Database {
         match_value: &str,
     ) -> Result<i64> {
         let now = chrono::Utc::now().timestamp();
+        let new_priority = self.next_rule_priority();
+        let (match_value, condition) = resolve_rule_condition(rule_type, match_value)?;
+
+        self.conn.execute(
+            "INSERT INTO project_rules (project_id, rule_type, match_value, condition, priority, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![project_id, rule_type, match_value, condition, new_priority, now],
+        )?;
 
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Priority given to newly created rules (after all existing ones)
+    pub fn next_rule_priority(&self) -> i64 {
         let max_priority: i64 = self
             .conn
             .query_row(
@@ -888,54 +1857,49 @@ impl Database {
             )
             .unwrap_or(-1);
 
-        let new_priority = max_priority + 1;
-
-        self.conn.execute(
-            "INSERT INTO project_rules (project_id, rule_type, match_value, priority, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-            rusqlite::params![project_id, rule_type, match_value, new_priority, now],
-        )?;
-
-        Ok(self.conn.last_insert_rowid())
+        max_priority + 1
     }
 
     pub fn get_project_rules(&self, project_id: Option<i64>) -> Result<Vec<ProjectRule>> {
         let query = if project_id.is_some() {
-            "SELECT id, project_id, rule_type, match_value, priority, created_at FROM project_rules WHERE project_id = ?1 ORDER BY priority ASC"
+            "SELECT id, project_id, rule_type, match_value, condition, priority, created_at FROM project_rules WHERE project_id = ?1 ORDER BY priority ASC"
         } else {
-            "SELECT id, project_id, rule_type, match_value, priority, created_at FROM project_rules ORDER BY priority ASC"
+            "SELECT id, project_id, rule_type, match_value, condition, priority, created_at FROM project_rules ORDER BY priority ASC"
         };
 
         let mut stmt = self.conn.prepare(query)?;
 
         let rules = if let Some(pid) = project_id {
-            stmt.query_map([pid], |row| {
-                Ok(ProjectRule {
-                    id: Some(row.get(0)?),
-                    project_id: row.get(1)?,
-                    rule_type: row.get(2)?,
-                    match_value: row.get(3)?,
-                    priority: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
-            .collect::<Result<Vec<_>>>()?
+            stmt.query_map([pid], project_rule_from_row)?
+                .collect::<Result<Vec<_>>>()?
         } else {
-            stmt.query_map([], |row| {
-                Ok(ProjectRule {
-                    id: Some(row.get(0)?),
-                    project_id: row.get(1)?,
-                    rule_type: row.get(2)?,
-                    match_value: row.get(3)?,
-                    priority: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
-            .collect::<Result<Vec<_>>>()?
+            stmt.query_map([], project_rule_from_row)?
+                .collect::<Result<Vec<_>>>()?
         };
 
         Ok(rules)
     }
 
+    /// The rule occupying a (rule_type, match_value) slot, compared in canonical form
+    pub fn find_project_rule(
+        &self,
+        rule_type: &str,
+        match_value: &str,
+    ) -> Result<Option<ProjectRule>> {
+        let (match_value, _) = resolve_rule_condition(rule_type, match_value)?;
+        let result = self.conn.query_row(
+            "SELECT id, project_id, rule_type, match_value, condition, priority, created_at FROM project_rules WHERE rule_type = ?1 AND match_value = ?2",
+            rusqlite::params![rule_type, match_value],
+            project_rule_from_row,
+        );
+
+        match result {
+            Ok(rule) => Ok(Some(rule)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn update_project_rule(
         &self,
         rule_id: i64,
@@ -943,9 +1907,10 @@ impl Database {
         rule_type: &str,
         match_value: &str,
     ) -> Result<()> {
+        let (match_value, condition) = resolve_rule_condition(rule_type, match_value)?;
         self.conn.execute(
-            "UPDATE project_rules SET project_id = ?1, rule_type = ?2, match_value = ?3 WHERE id = ?4",
-            rusqlite::params![project_id, rule_type, match_value, rule_id],
+            "UPDATE project_rules SET project_id = ?1, rule_type = ?2, match_value = ?3, condition = ?4 WHERE id = ?5",
+            rusqlite::params![project_id, rule_type, match_value, condition, rule_id],
         )?;
         Ok(())
     }
@@ -966,61 +1931,67 @@ impl Database {
         Ok(())
     }
 
-    pub fn apply_rules_to_events(&self) -> Result<usize> {
-        self.conn
-            .execute("UPDATE events SET project_id = NULL", [])?;
-
-        let rules = self.get_project_rules(None)?;
-        let mut updated_count = 0;
-
-        for rule in rules {
-            let count = match rule.rule_type.as_str() {
-                "organizer" => self.conn.execute(
-                    "UPDATE events
-                         SET project_id = ?1
-                         WHERE event_type = 'calendar'
-                         AND project_id IS NULL
-                         AND organizer_id IN (SELECT id FROM contacts WHERE name = ?2)",
-                    rusqlite::params![rule.project_id, rule.match_value],
-                )?,
-                "title_pattern" => self.conn.execute(
-                    "UPDATE events
-                         SET project_id = ?1
-                         WHERE event_type = 'calendar'
-                         AND project_id IS NULL
-                         AND lower(title) LIKE lower(?2)",
-                    rusqlite::params![rule.project_id, format!("%{}%", rule.match_value)],
-                )?,
-                "repository" => self.conn.execute(
+    /// Record the rule (if any) that matched a single event.
+    /// Returns true if that newly assigned the event, or moved it to another rule or project;
+    /// manual assignments are left alone.
+    pub fn apply_rule_match(&self, event_id: i64, rule: Option<&ProjectRule>) -> Result<bool> {
+        match rule {
+            Some(rule) => {
+                let updated = self.conn.execute(
                     "UPDATE events
-                         SET project_id = ?1
-                         WHERE (event_type = 'git' OR event_type = 'browser_history')
-                         AND project_id IS NULL
-                         AND repository_path = ?2",
-                    rusqlite::params![rule.project_id, rule.match_value],
-                )?,
-                "url_pattern" => self.conn.execute(
-                    "UPDATE events
-                         SET project_id = ?1
-                         WHERE event_type = 'browser_history'
-                         AND project_id IS NULL
-                         AND json_extract(type_specific_data, '$.url') LIKE ?2",
-                    rusqlite::params![rule.project_id, rule.match_value],
-                )?,
-                "domain" => self.conn.execute(
+                     SET project_id = ?1, assignment_source = 'rule', assignment_rule_id = ?2
+                     WHERE id = ?3 AND assignment_source != 'manual'
+                       AND (project_id IS NOT ?1 OR assignment_rule_id IS NOT ?2)",
+                    rusqlite::params![rule.project_id, rule.id, event_id],
+                )?;
+                Ok(updated > 0)
+            }
+            None => {
+                // The event no longer matches any rule, so drop a stale rule assignment
+                self.conn.execute(
                     "UPDATE events
-                         SET project_id = ?1
-                         WHERE event_type = 'browser_history'
-                         AND project_id IS NULL
-                         AND domain = ?2",
-                    rusqlite::params![rule.project_id, rule.match_value],
-                )?,
-                _ => 0,
+                     SET project_id = NULL, assignment_source = 'unassigned', assignment_rule_id = NULL
+                     WHERE id = ?1 AND assignment_source = 'rule'",
+                    [event_id],
+                )?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Re-evaluate rules for every event that isn't manually assigned.
+    /// Rules are applied in priority order; the first matching rule claims an event.
+    pub fn apply_rules_to_events(&self) -> Result<RuleApplicationResult> {
+        let engine = RuleEngine::load(self)?;
+        let events = self.get_all_events()?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut assigned_count = 0;
+        let mut pinned_event_ids = Vec::new();
+
+        for event in &events {
+            let Some(event_id) = event.id else {
+                continue;
             };
-            updated_count += count;
+            let matched = engine.find_match(event);
+
+            if event.assignment_source == AssignmentSource::Manual {
+                if matched.is_some() {
+                    pinned_event_ids.push(event_id);
+                }
+                continue;
+            }
+
+            if self.apply_rule_match(event_id, matched)? {
+                assigned_count += 1;
+            }
         }
+        tx.commit()?;
 
-        Ok(updated_count)
+        Ok(RuleApplicationResult {
+            assigned_count,
+            pinned_event_ids,
+        })
     }
 
     // Contact operations
@@ -1057,3 +2028,639 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
 }
+
+fn project_rule_from_row(row: &rusqlite::Row) -> Result<ProjectRule> {
+    let condition: Option<String> = row.get(4)?;
+    Ok(ProjectRule {
+        id: Some(row.get(0)?),
+        project_id: row.get(1)?,
+        rule_type: row.get(2)?,
+        match_value: row.get(3)?,
+        condition: condition.and_then(|json| serde_json::from_str(&json).ok()),
+        priority: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Validate a rule and return its (normalized match_value, condition JSON) for storage
+fn resolve_rule_condition(rule_type: &str, match_value: &str) -> Result<(String, String)> {
+    let condition = RuleCondition::from_rule(rule_type, match_value)
+        .map_err(rusqlite::Error::InvalidParameterName)?;
+    let condition_json = to_json(&condition)?;
+
+    // Condition rules are stored in canonical form so the UNIQUE constraint catches duplicates
+    let match_value = if rule_type == "condition" {
+        condition_json.clone()
+    } else {
+        match_value.to_string()
+    };
+
+    Ok((match_value, condition_json))
+}
+
+/// Searchable text from `type_specific_data` for the row alias `row` (e.g. "new")
+fn search_details_sql(row: &str) -> String {
+    let fields = [
+        "location",
+        "notes",
+        "url",
+        "page_title",
+        "ref_name",
+        "repository_name",
+        "body",
+    ]
+    .iter()
+    .map(|field| format!("json_extract({}.type_specific_data, '$.{}')", row, field))
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    // concat_ws skips NULLs, so absent fields don't leave runs of separators
+    format!(
+        "CASE WHEN json_valid({row}.type_specific_data) THEN concat_ws(' ', {fields}) ELSE '' END",
+        row = row,
+        fields = fields
+    )
+}
+
+/// SQL condition keeping browser events only on work domains, like `get_events` does
+fn work_domain_sql(row: &str) -> String {
+    format!(
+        "({row}.event_type != 'browser_history' OR {row}.domain IN (SELECT domain FROM work_domains))",
+        row = row
+    )
+}
+
+/// SQL condition matching calendar events I won't attend: cancelled, or declined by me
+fn skipped_meeting_sql(row: &str) -> String {
+    format!(
+        "({row}.event_type = 'calendar'
+          AND (json_extract({row}.type_specific_data, '$.status') IS 'cancelled'
+               OR json_extract({row}.type_specific_data, '$.participant_status') IS 'declined'))",
+        row = row
+    )
+}
+
+/// Turn free text into an FTS5 query: every word must match, the last one as a prefix.
+/// Quoting each word keeps punctuation from being read as query syntax.
+fn fts_query(input: &str) -> Option<String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    let last = words.last()?;
+    let mut query = words[..words.len() - 1].to_vec();
+    query.push(format!("{}*", last));
+    Some(query.join(" "))
+}
+
+/// Build the stored form of a note (without an external id)
+fn note_event(note: &NoteInput) -> Result<Event> {
+    if note.title.trim().is_empty() {
+        return Err(rusqlite::Error::InvalidParameterName(
+            "Note title cannot be empty".to_string(),
+        ));
+    }
+    if let Some(duration_minutes) = note.duration_minutes {
+        validate_duration(duration_minutes)?;
+    }
+
+    Ok(Event {
+        id: None,
+        event_type: "note".to_string(),
+        title: note.title.trim().to_string(),
+        start_date: note.start_date,
+        end_date: note.start_date + note.duration_minutes.unwrap_or(0) * 60,
+        external_id: None,
+        external_link: None,
+        type_specific_data: Some(to_json(&NoteEventData {
+            body: note.body.clone(),
+        })?),
+        project_id: note.project_id,
+        organizer_id: None,
+        repository_path: None,
+        domain: None,
+        series_id: None,
+        // A project chosen by the user is a manual assignment that rules won't override
+        assignment_source: if note.project_id.is_some() {
+            AssignmentSource::Manual
+        } else {
+            AssignmentSource::Unassigned
+        },
+        assignment_rule_id: None,
+        created_at: 0,
+        updated_at: 0,
+    })
+}
+
+fn validate_duration(duration_minutes: i64) -> Result<()> {
+    if !(1..=24 * 60).contains(&duration_minutes) {
+        return Err(rusqlite::Error::InvalidParameterName(format!(
+            "Duration must be between 1 minute and 24 hours, got {} minutes",
+            duration_minutes
+        )));
+    }
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Stored form of a unit enum: its serde name, e.g. "allow"
+fn enum_to_db<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => Ok(name),
+        Ok(other) => Err(rusqlite::Error::ToSqlConversionFailure(
+            format!("Expected a unit variant, got {}", other).into(),
+        )),
+        Err(e) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+    }
+}
+
+fn enum_from_db<T: serde::de::DeserializeOwned>(row: &rusqlite::Row, index: usize) -> Result<T> {
+    let name: String = row.get(index)?;
+    serde_json::from_value(serde_json::Value::String(name)).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Database {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        db.init_schema().unwrap();
+        db
+    }
+
+    fn calendar_event(external_id: &str, title: &str, start: i64, end: i64) -> Event {
+        Event {
+            id: None,
+            event_type: "calendar".to_string(),
+            title: title.to_string(),
+            start_date: start,
+            end_date: end,
+            external_id: Some(external_id.to_string()),
+            external_link: None,
+            type_specific_data: None,
+            project_id: None,
+            organizer_id: None,
+            repository_path: None,
+            domain: None,
+            series_id: None,
+            assignment_source: AssignmentSource::Unassigned,
+            assignment_rule_id: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_fts_query_quotes_words_and_prefixes_the_last() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(
+            fts_query("design doc"),
+            Some("\"design\" \"doc\"*".to_string())
+        );
+        assert_eq!(
+            fts_query("ACME-123 \"x"),
+            Some("\"ACME-123\" \"\"\"x\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn test_delete_missing_calendar_events_only_in_window() {
+        let db = test_db();
+        let project = db.create_project("Acme", None).unwrap();
+        let (kept, _) = db
+            .upsert_event(&calendar_event("kept", "Kept", 1_000, 2_000))
+            .unwrap();
+        let (gone, _) = db
+            .upsert_event(&calendar_event("gone", "Gone", 1_000, 2_000))
+            .unwrap();
+        let (overlapping, _) = db
+            .upsert_event(&calendar_event("overlap", "Overlapping", 500, 1_500))
+            .unwrap();
+        let (outside, _) = db
+            .upsert_event(&calendar_event("outside", "Outside", 5_000, 6_000))
+            .unwrap();
+        let (pinned, _) = db
+            .upsert_event(&calendar_event("pinned", "Pinned", 1_000, 2_000))
+            .unwrap();
+        db.assign_event_to_project(pinned, Some(project)).unwrap();
+
+        let current = HashSet::from(["kept".to_string()]);
+        assert_eq!(
+            db.delete_missing_calendar_events(1_000, 3_000, &current)
+                .unwrap(),
+            2
+        );
+        for (id, present) in [
+            (kept, true),
+            (gone, false),
+            (overlapping, false),
+            (outside, true),
+            (pinned, true),
+        ] {
+            assert_eq!(db.get_event(id).unwrap().is_some(), present);
+        }
+
+        // Once unpinned, the next reconciliation removes it
+        db.assign_event_to_project(pinned, None).unwrap();
+        assert_eq!(
+            db.delete_missing_calendar_events(1_000, 3_000, &current)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_aggregate_events_skips_cancelled_and_declined_meetings() {
+        let db = test_db();
+        let mut meetings = vec![calendar_event("kept", "Kept", 0, 3_600)];
+        for (id, data) in [
+            ("cancelled", r#"{"is_all_day":false,"status":"cancelled"}"#),
+            (
+                "declined",
+                r#"{"is_all_day":false,"participant_status":"declined"}"#,
+            ),
+            (
+                "accepted",
+                r#"{"is_all_day":false,"participant_status":"accepted"}"#,
+            ),
+        ] {
+            let mut meeting = calendar_event(id, id, 0, 1_800);
+            meeting.type_specific_data = Some(data.to_string());
+            meetings.push(meeting);
+        }
+        for meeting in &meetings {
+            db.upsert_event(meeting).unwrap();
+        }
+
+        let rows = db
+            .aggregate_events(AggregateDimension::EventType, 0, 10_000)
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].event_count, 2);
+        assert_eq!(rows[0].duration_seconds, 5_400);
+    }
+
+    #[test]
+    fn test_search_events_follows_event_changes() {
+        let db = test_db();
+        let titles = |query: &str, filters: &SearchFilters| -> Vec<String> {
+            db.search_events(query, filters)
+                .unwrap()
+                .into_iter()
+                .map(|result| result.event.title)
+                .collect()
+        };
+        let mut review = calendar_event("review", "Design review", 1_000, 2_000);
+        review.type_specific_data =
+            Some(r#"{"is_all_day":false,"location":"Room Kepler","notes":null}"#.to_string());
+        db.upsert_event(&review).unwrap();
+        db.upsert_event(&calendar_event("retro", "Sprint retro", 5_000, 6_000))
+            .unwrap();
+
+        let none = SearchFilters::default();
+        assert_eq!(titles("kepl", &none), vec!["Design review"]);
+
+        review.title = "Architecture review".to_string();
+        db.upsert_event(&review).unwrap();
+        assert!(titles("design", &none).is_empty());
+        assert_eq!(titles("architecture", &none), vec!["Architecture review"]);
+
+        let later = SearchFilters {
+            start_date: Some(3_000),
+            ..SearchFilters::default()
+        };
+        assert!(titles("review", &later).is_empty());
+        assert_eq!(titles("retro", &later), vec!["Sprint retro"]);
+        let git_only = SearchFilters {
+            event_types: vec!["git".to_string()],
+            ..SearchFilters::default()
+        };
+        assert!(titles("retro", &git_only).is_empty());
+
+        db.delete_event_by_external_id("calendar", "retro").unwrap();
+        assert!(titles("retro", &none).is_empty());
+    }
+
+    #[test]
+    fn test_search_index_is_filled_from_existing_events() {
+        let db = test_db();
+        db.upsert_event(&calendar_event("retro", "Sprint retro", 0, 1_000))
+            .unwrap();
+        // As in a database from before the index existed
+        db.conn
+            .execute_batch(
+                "DROP TRIGGER events_fts_insert;
+                 DROP TRIGGER events_fts_delete;
+                 DROP TRIGGER events_fts_update;
+                 DROP TABLE events_fts;",
+            )
+            .unwrap();
+
+        db.init_schema().unwrap();
+        let results = db
+            .search_events("retro", &SearchFilters::default())
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet, "Sprint **retro**");
+    }
+
+    #[test]
+    fn test_notes_are_events_that_outlive_cleared_data() {
+        let db = test_db();
+        let project = db.create_project("Acme", None).unwrap();
+        let (meeting, _) = db
+            .upsert_event(&calendar_event("sync", "Sync", 1_000, 2_000))
+            .unwrap();
+        let mut input = NoteInput {
+            title: " Incident write-up ".to_string(),
+            body: "Root cause: expired cert".to_string(),
+            start_date: 1_500,
+            duration_minutes: Some(30),
+            project_id: Some(project),
+        };
+        let note = db.create_note(&input).unwrap();
+
+        let events = db.get_events(Some(0), Some(10_000)).unwrap();
+        let stored = events.iter().find(|e| e.id == Some(note)).unwrap();
+        assert_eq!(stored.event_type, "note");
+        assert_eq!(stored.title, "Incident write-up");
+        assert_eq!(stored.end_date, 1_500 + 30 * 60);
+        assert_eq!(stored.assignment_source, AssignmentSource::Manual);
+        assert_eq!(
+            db.search_events("cert", &SearchFilters::default())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(db.get_export_events(0, 10_000).unwrap().len(), 2);
+
+        input.title = "Postmortem".to_string();
+        input.project_id = None;
+        db.update_note(note, &input).unwrap();
+        let stored = db.get_event(note).unwrap().unwrap();
+        assert_eq!(stored.title, "Postmortem");
+        assert_eq!(stored.project_id, None);
+
+        // Only notes can be edited or deleted this way
+        assert!(db.update_note(meeting, &input).is_err());
+        assert!(db.delete_note(meeting).is_err());
+        assert_eq!(db.get_event(meeting).unwrap().unwrap().title, "Sync");
+
+        db.clear_event_data().unwrap();
+        assert!(db.get_event(meeting).unwrap().is_none());
+        assert!(db.get_event(note).unwrap().is_some());
+
+        db.delete_note(note).unwrap();
+        assert!(db.get_event(note).unwrap().is_none());
+        assert!(db.delete_note(note).is_err());
+    }
+
+    #[test]
+    fn test_manual_assignment_is_pinned_against_sync_and_rules() {
+        let db = test_db();
+        let acme = db.create_project("Acme", None).unwrap();
+        let globex = db.create_project("Globex", None).unwrap();
+        db.create_project_rule(acme, "title_pattern", "standup")
+            .unwrap();
+        let (ruled, _) = db
+            .upsert_event(&calendar_event("ruled", "Standup", 0, 900))
+            .unwrap();
+        let (manual, _) = db
+            .upsert_event(&calendar_event("manual", "Standup (Globex)", 0, 900))
+            .unwrap();
+        db.assign_event_to_project(manual, Some(globex)).unwrap();
+
+        let result = db.apply_rules_to_events().unwrap();
+        assert_eq!(result.assigned_count, 1);
+        assert_eq!(result.pinned_event_ids, vec![manual]);
+
+        // Sync brings the same event back, unassigned or claimed by a rule
+        db.upsert_event(&calendar_event("manual", "Standup (Globex)", 0, 900))
+            .unwrap();
+        let mut claimed = calendar_event("manual", "Standup (Globex)", 0, 900);
+        claimed.project_id = Some(acme);
+        claimed.assignment_source = AssignmentSource::Rule;
+        db.upsert_event(&claimed).unwrap();
+        db.apply_rules_to_events().unwrap();
+
+        let manual = db.get_event(manual).unwrap().unwrap();
+        assert_eq!(manual.project_id, Some(globex));
+        assert_eq!(manual.assignment_source, AssignmentSource::Manual);
+        assert_eq!(manual.assignment_rule_id, None);
+        let ruled = db.get_event(ruled).unwrap().unwrap();
+        assert_eq!(ruled.project_id, Some(acme));
+        assert_eq!(ruled.assignment_source, AssignmentSource::Rule);
+    }
+
+    #[test]
+    fn test_time_entries_keep_their_sources_across_cleared_data() {
+        let db = test_db();
+        let acme = db.create_project("Acme", None).unwrap();
+        let globex = db.create_project("Globex", None).unwrap();
+        let day = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let (review, _) = db
+            .upsert_event(&calendar_event("review", "Review", 1_000, 2_000))
+            .unwrap();
+        let (retro, _) = db
+            .upsert_event(&calendar_event("retro", "Retro", 3_000, 4_000))
+            .unwrap();
+
+        let id = db
+            .create_time_entry(day, acme, 90, Some("Planning"), &[retro, review])
+            .unwrap();
+        let entries = db.get_time_entries(day, day).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].duration_minutes, 90);
+        assert_eq!(entries[0].event_ids, vec![review, retro]);
+        assert!(db.create_time_entry(day, acme, 30, None, &[999]).is_err());
+        assert!(db.create_time_entry(day, acme, 0, None, &[]).is_err());
+        assert_eq!(db.get_time_entries(day, day).unwrap().len(), 1);
+
+        db.update_time_entry(id, day, globex, 60, None, &[review])
+            .unwrap();
+        let entry = &db.get_time_entries(day, day).unwrap()[0];
+        assert_eq!(
+            (
+                entry.project_id,
+                entry.duration_minutes,
+                entry.note.as_deref()
+            ),
+            (globex, 60, None)
+        );
+        assert_eq!(entry.event_ids, vec![review]);
+        assert!(db.update_time_entry(999, day, acme, 60, None, &[]).is_err());
+
+        // Entries stay, and find their source again once it is synced back
+        db.clear_event_data().unwrap();
+        assert!(db.get_time_entries(day, day).unwrap()[0]
+            .event_ids
+            .is_empty());
+        let (review, _) = db
+            .upsert_event(&calendar_event("review", "Review", 1_000, 2_000))
+            .unwrap();
+        assert_eq!(
+            db.get_time_entries(day, day).unwrap()[0].event_ids,
+            vec![review]
+        );
+
+        db.delete_time_entry(id).unwrap();
+        assert!(db.get_time_entries(day, day).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_rule_match_reports_only_changes() {
+        let db = test_db();
+        let acme = db.create_project("Acme", None).unwrap();
+        let globex = db.create_project("Globex", None).unwrap();
+        let acme_rule = db
+            .create_project_rule(acme, "title_pattern", "standup")
+            .unwrap();
+        let globex_rule = db
+            .create_project_rule(globex, "title_pattern", "stand")
+            .unwrap();
+        let rules = db.get_project_rules(None).unwrap();
+        let rule = |id: i64| rules.iter().find(|rule| rule.id == Some(id));
+        let (event, _) = db
+            .upsert_event(&calendar_event("standup", "Standup", 0, 900))
+            .unwrap();
+
+        assert!(db.apply_rule_match(event, rule(acme_rule)).unwrap());
+        // A re-sync of an event the same rule already claimed changes nothing
+        assert!(!db.apply_rule_match(event, rule(acme_rule)).unwrap());
+        assert!(db.apply_rule_match(event, rule(globex_rule)).unwrap());
+        // The higher priority rule takes it back, once
+        assert_eq!(db.apply_rules_to_events().unwrap().assigned_count, 1);
+        assert_eq!(db.apply_rules_to_events().unwrap().assigned_count, 0);
+    }
+
+    #[test]
+    fn test_browser_sync_cursors_are_per_profile() {
+        let db = test_db();
+        assert_eq!(db.get_browser_sync_cursor("/work").unwrap(), None);
+        db.set_browser_sync_cursor("/work", 1_000).unwrap();
+        db.set_browser_sync_cursor("/work", 2_000).unwrap();
+        assert_eq!(db.get_browser_sync_cursor("/work").unwrap(), Some(2_000));
+        // A profile added later starts from scratch
+        assert_eq!(db.get_browser_sync_cursor("/personal").unwrap(), None);
+
+        db.clear_event_data().unwrap();
+        assert_eq!(db.get_browser_sync_cursor("/work").unwrap(), None);
+    }
+
+    #[test]
+    fn test_browser_filter_rules_round_trip() {
+        use crate::privacy::{FilterAction, FilterField, PatternKind};
+
+        let db = test_db();
+        let mut rule = BrowserFilterRule {
+            id: None,
+            action: FilterAction::Allow,
+            field: FilterField::Domain,
+            kind: PatternKind::Glob,
+            pattern: "  *.acme.com ".to_string(),
+        };
+        let id = db.create_browser_filter_rule(&rule).unwrap();
+        let stored = db.get_browser_filter_rules().unwrap();
+        assert_eq!(
+            stored,
+            vec![BrowserFilterRule {
+                id: Some(id),
+                pattern: "*.acme.com".to_string(),
+                ..rule.clone()
+            }]
+        );
+
+        rule.action = FilterAction::Deny;
+        rule.field = FilterField::Title;
+        rule.kind = PatternKind::Regex;
+        rule.pattern = r"^salary\b".to_string();
+        db.update_browser_filter_rule(id, &rule).unwrap();
+        let stored = &db.get_browser_filter_rules().unwrap()[0];
+        assert_eq!(
+            (stored.action, stored.field, stored.kind),
+            (FilterAction::Deny, FilterField::Title, PatternKind::Regex)
+        );
+
+        // Invalid regexes are rejected and leave the stored rule alone
+        rule.pattern = "(unclosed".to_string();
+        assert!(db.create_browser_filter_rule(&rule).is_err());
+        assert!(db.update_browser_filter_rule(id, &rule).is_err());
+        assert_eq!(
+            db.get_browser_filter_rules().unwrap()[0].pattern,
+            r"^salary\b"
+        );
+
+        db.delete_browser_filter_rule(id).unwrap();
+        assert!(db.get_browser_filter_rules().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_events_matches_the_timeline() {
+        let db = test_db();
+        let mut holiday = calendar_event("holiday", "Holiday", 0, 86_400);
+        holiday.type_specific_data = Some(r#"{"is_all_day":true}"#.to_string());
+        db.upsert_event(&holiday).unwrap();
+        db.upsert_event(&calendar_event("sync", "Sync", 0, 1_800))
+            .unwrap();
+        let mut visit = calendar_event("https://docs.acme.com/", "Docs", 600, 600);
+        visit.event_type = "browser_history".to_string();
+        visit.domain = Some("docs.acme.com".to_string());
+        db.upsert_event(&visit).unwrap();
+
+        let totals = |db: &Database| -> Vec<(Option<String>, i64, i64)> {
+            db.aggregate_events(AggregateDimension::EventType, 0, 86_400)
+                .unwrap()
+                .into_iter()
+                .map(|row| (row.key, row.event_count, row.duration_seconds))
+                .collect()
+        };
+        // Without work domains the timeline shows no browser events, so neither do totals
+        assert_eq!(totals(&db), vec![(Some("calendar".to_string()), 1, 1_800)]);
+        assert_eq!(db.get_events(Some(0), Some(86_400)).unwrap().len(), 2);
+
+        db.conn
+            .execute(
+                "INSERT INTO work_domains (domain, created_at) VALUES ('docs.acme.com', 0)",
+                [],
+            )
+            .unwrap();
+        assert_eq!(totals(&db).len(), 2);
+    }
+
+    #[test]
+    fn test_search_hides_browser_events_off_work_domains() {
+        let db = test_db();
+        let mut visit = calendar_event("https://news.example.com/", "Budget news", 0, 0);
+        visit.event_type = "browser_history".to_string();
+        visit.domain = Some("news.example.com".to_string());
+        db.upsert_event(&visit).unwrap();
+        db.upsert_event(&calendar_event("budget", "Budget review", 0, 1_800))
+            .unwrap();
+
+        // No work domains at all, like the timeline: browser events stay hidden
+        let results = db
+            .search_events("budget", &SearchFilters::default())
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].event.title, "Budget review");
+
+        db.conn
+            .execute(
+                "INSERT INTO work_domains (domain, created_at) VALUES ('news.example.com', 0)",
+                [],
+            )
+            .unwrap();
+        assert_eq!(
+            db.search_events("budget", &SearchFilters::default())
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,394 @@
+use crate::db::{Database, Event};
+use crate::ledger::local_day_bounds;
+use crate::rules::{is_all_day, is_cancelled_or_declined};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Settings keys for the session thresholds (values in minutes)
+const GIT_GAP_SETTING: &str = "estimate_git_gap_minutes";
+const BROWSER_GAP_SETTING: &str = "estimate_browser_gap_minutes";
+const ACTIVITY_SETTING: &str = "estimate_activity_minutes";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimationConfig {
+    pub git_gap: i64,           // Max seconds between git activity in one session
+    pub browser_gap: i64,       // Max seconds between page visits in one session
+    pub activity_duration: i64, // Seconds credited after the last activity of a session
+}
+
+impl Default for EstimationConfig {
+    fn default() -> Self {
+        EstimationConfig {
+            git_gap: 30 * 60,
+            browser_gap: 10 * 60,
+            activity_duration: 5 * 60,
+        }
+    }
+}
+
+impl EstimationConfig {
+    pub fn load(db: &Database) -> rusqlite::Result<Self> {
+        let defaults = EstimationConfig::default();
+        let minutes = |key: &str, default: i64| -> rusqlite::Result<i64> {
+            Ok(db
+                .get_setting(key)?
+                .and_then(|value| value.trim().parse::<i64>().ok())
+                .filter(|minutes| *minutes >= 0)
+                .map_or(default, |minutes| minutes * 60))
+        };
+
+        Ok(EstimationConfig {
+            git_gap: minutes(GIT_GAP_SETTING, defaults.git_gap)?,
+            browser_gap: minutes(BROWSER_GAP_SETTING, defaults.browser_gap)?,
+            activity_duration: minutes(ACTIVITY_SETTING, defaults.activity_duration)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockSource {
+    Calendar,
+    Git,
+    Browser,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TimeBlock {
+    pub project_id: Option<i64>,
+    pub source: BlockSource,
+    pub start: i64, // Unix timestamp in seconds (UTC)
+    pub end: i64,
+    pub event_ids: Vec<i64>, // Events that make up the block
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DayEstimate {
+    pub date: NaiveDate,
+    pub blocks: Vec<TimeBlock>,
+    pub project_totals: Vec<ProjectEstimate>,
+    pub total_minutes: i64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ProjectEstimate {
+    pub project_id: Option<i64>, // None for unassigned time
+    pub minutes: i64,
+}
+
+/// Estimate time per project for each local day from `start` to `end` (inclusive)
+pub fn estimate_range(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> rusqlite::Result<Vec<DayEstimate>> {
+    let config = EstimationConfig::load(db)?;
+    let (range_start, _) = local_day_bounds(start);
+    let (_, range_end) = local_day_bounds(end);
+    // Meetings that started before the range still count for the part inside it
+    let events = db.get_events_overlapping(range_start, range_end)?;
+
+    let mut days = Vec::new();
+    for date in start.iter_days().take_while(|date| *date <= end) {
+        let (day_start, day_end) = local_day_bounds(date);
+        // Calendar events can run past midnight; activity belongs to the day it happened on
+        let day_events: Vec<Event> = events
+            .iter()
+            .filter(|event| {
+                event.start_date < day_end
+                    && (event.start_date >= day_start
+                        || (event.event_type == "calendar" && event.end_date > day_start))
+            })
+            .cloned()
+            .collect();
+
+        let blocks: Vec<TimeBlock> = estimate_blocks(&day_events, &config)
+            .into_iter()
+            .filter_map(|block| clip(block, day_start, day_end))
+            .collect();
+        let project_totals = project_totals(&blocks);
+
+        days.push(DayEstimate {
+            date,
+            total_minutes: project_totals.iter().map(|total| total.minutes).sum(),
+            blocks,
+            project_totals,
+        });
+    }
+
+    Ok(days)
+}
+
+/// Turn events into non-overlapping blocks. Calendar events I attend claim their time first; git
+/// and browser activity is grouped into sessions per project and only fills the time left over.
+pub fn estimate_blocks(events: &[Event], config: &EstimationConfig) -> Vec<TimeBlock> {
+    let mut candidates: Vec<TimeBlock> = events
+        .iter()
+        .filter(|event| {
+            event.event_type == "calendar"
+                && event.end_date > event.start_date
+                && !is_all_day(event)
+                && !is_cancelled_or_declined(event)
+        })
+        .map(|event| TimeBlock {
+            project_id: event.project_id,
+            source: BlockSource::Calendar,
+            start: event.start_date,
+            end: event.end_date,
+            event_ids: event.id.into_iter().collect(),
+        })
+        .collect();
+    candidates.extend(sessions(
+        events,
+        "git",
+        BlockSource::Git,
+        config.git_gap,
+        config,
+    ));
+    candidates.extend(sessions(
+        events,
+        "browser_history",
+        BlockSource::Browser,
+        config.browser_gap,
+        config,
+    ));
+
+    // Stable sort keeps calendar, then git, then browser, each in start order
+    candidates.sort_by_key(|block| (block.source, block.start));
+
+    let mut claimed: Vec<(i64, i64)> = Vec::new();
+    let mut blocks = Vec::new();
+    for candidate in candidates {
+        for (start, end) in subtract(candidate.start, candidate.end, &claimed) {
+            claimed.push((start, end));
+            blocks.push(TimeBlock {
+                start,
+                end,
+                ..candidate.clone()
+            });
+        }
+    }
+
+    blocks.sort_by_key(|block| block.start);
+    blocks
+}
+
+/// Cluster instantaneous activity of one type into per-project sessions
+fn sessions(
+    events: &[Event],
+    event_type: &str,
+    source: BlockSource,
+    gap: i64,
+    config: &EstimationConfig,
+) -> Vec<TimeBlock> {
+    let mut by_project: BTreeMap<Option<i64>, Vec<&Event>> = BTreeMap::new();
+    for event in events.iter().filter(|e| e.event_type == event_type) {
+        by_project.entry(event.project_id).or_default().push(event);
+    }
+
+    let mut sessions = Vec::new();
+    for (project_id, mut events) in by_project {
+        events.sort_by_key(|event| event.start_date);
+
+        let mut current: Option<TimeBlock> = None;
+        let mut last_activity = i64::MIN;
+        for event in events {
+            let activity_end = event.end_date.max(event.start_date);
+            match current.as_mut() {
+                Some(session) if event.start_date - last_activity <= gap => {
+                    session.end = session.end.max(activity_end + config.activity_duration);
+                    session.event_ids.extend(event.id);
+                }
+                _ => {
+                    sessions.extend(current.take());
+                    current = Some(TimeBlock {
+                        project_id,
+                        source,
+                        start: event.start_date,
+                        end: activity_end + config.activity_duration,
+                        event_ids: event.id.into_iter().collect(),
+                    });
+                }
+            }
+            last_activity = last_activity.max(activity_end);
+        }
+        sessions.extend(current);
+    }
+
+    sessions
+}
+
+/// Parts of [start, end) not covered by any of the claimed intervals
+fn subtract(start: i64, end: i64, claimed: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut pieces = vec![(start, end)];
+    for &(claimed_start, claimed_end) in claimed {
+        pieces = pieces
+            .into_iter()
+            .flat_map(|(s, e)| {
+                if claimed_end <= s || claimed_start >= e {
+                    vec![(s, e)]
+                } else {
+                    [(s, claimed_start), (claimed_end, e)]
+                        .into_iter()
+                        .filter(|(s, e)| s < e)
+                        .collect()
+                }
+            })
+            .collect();
+    }
+    pieces
+}
+
+fn clip(block: TimeBlock, day_start: i64, day_end: i64) -> Option<TimeBlock> {
+    let start = block.start.max(day_start);
+    let end = block.end.min(day_end);
+    (start < end).then_some(TimeBlock {
+        start,
+        end,
+        ..block
+    })
+}
+
+fn project_totals(blocks: &[TimeBlock]) -> Vec<ProjectEstimate> {
+    let mut seconds: BTreeMap<Option<i64>, i64> = BTreeMap::new();
+    for block in blocks {
+        *seconds.entry(block.project_id).or_default() += block.end - block.start;
+    }
+
+    let mut totals: Vec<ProjectEstimate> = seconds
+        .into_iter()
+        .map(|(project_id, seconds)| ProjectEstimate {
+            project_id,
+            minutes: (seconds + 30) / 60,
+        })
+        .collect();
+    totals.sort_by_key(|total| std::cmp::Reverse(total.minutes));
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: i64, event_type: &str, start: i64, end: i64, project_id: Option<i64>) -> Event {
+        Event {
+            id: Some(id),
+            event_type: event_type.to_string(),
+            title: String::new(),
+            start_date: start,
+            end_date: end,
+            external_id: None,
+            external_link: None,
+            type_specific_data: None,
+            project_id,
+            organizer_id: None,
+            repository_path: None,
+            domain: None,
+            series_id: None,
+            assignment_source: Default::default(),
+            assignment_rule_id: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    const MIN: i64 = 60;
+
+    #[test]
+    fn test_activity_is_clustered_into_sessions() {
+        let config = EstimationConfig::default();
+        let events = vec![
+            event(1, "git", 0, 0, Some(1)),
+            event(2, "git", 20 * MIN, 20 * MIN, Some(1)),
+            // More than the 30 minute git gap later, so a new session
+            event(3, "git", 60 * MIN, 60 * MIN, Some(1)),
+        ];
+
+        let blocks = estimate_blocks(&events, &config);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!((blocks[0].start, blocks[0].end), (0, 25 * MIN));
+        assert_eq!(blocks[0].event_ids, vec![1, 2]);
+        assert_eq!((blocks[1].start, blocks[1].end), (60 * MIN, 65 * MIN));
+    }
+
+    #[test]
+    fn test_calendar_time_takes_priority() {
+        let config = EstimationConfig::default();
+        let events = vec![
+            event(1, "calendar", 10 * MIN, 20 * MIN, Some(2)),
+            event(2, "browser_history", 0, 0, Some(1)),
+            event(3, "browser_history", 8 * MIN, 8 * MIN, Some(1)),
+            event(4, "browser_history", 18 * MIN, 18 * MIN, Some(1)),
+        ];
+
+        let blocks = estimate_blocks(&events, &config);
+        let spans: Vec<_> = blocks
+            .iter()
+            .map(|b| (b.source, b.project_id, b.start / MIN, b.end / MIN))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (BlockSource::Browser, Some(1), 0, 10),
+                (BlockSource::Calendar, Some(2), 10, 20),
+                (BlockSource::Browser, Some(1), 20, 23),
+            ]
+        );
+        assert_eq!(
+            project_totals(&blocks),
+            vec![
+                ProjectEstimate {
+                    project_id: Some(1),
+                    minutes: 13
+                },
+                ProjectEstimate {
+                    project_id: Some(2),
+                    minutes: 10
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_declined_meetings_claim_no_time() {
+        let config = EstimationConfig::default();
+        let mut declined = event(1, "calendar", 0, 30 * MIN, Some(2));
+        declined.type_specific_data =
+            Some(r#"{"is_all_day":false,"participant_status":"declined"}"#.to_string());
+        let events = vec![declined, event(2, "git", 10 * MIN, 10 * MIN, Some(1))];
+
+        let blocks = estimate_blocks(&events, &config);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].source, BlockSource::Git);
+    }
+
+    #[test]
+    fn test_meeting_running_into_the_range_counts_on_the_first_day() {
+        let db = Database::new(":memory:".into()).unwrap();
+        db.init_schema().unwrap();
+        let day = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let (day_start, _) = local_day_bounds(day);
+        let mut late_call = event(
+            0,
+            "calendar",
+            day_start - 30 * MIN,
+            day_start + 45 * MIN,
+            None,
+        );
+        late_call.id = None;
+        late_call.external_id = Some("late-call".to_string());
+        db.upsert_event(&late_call).unwrap();
+
+        let days = estimate_range(&db, day, day).unwrap();
+        assert_eq!(days[0].total_minutes, 45);
+        assert_eq!(days[0].blocks[0].start, day_start);
+    }
+
+    #[test]
+    fn test_subtract() {
+        assert_eq!(subtract(0, 10, &[(3, 5)]), vec![(0, 3), (5, 10)]);
+        assert_eq!(subtract(0, 10, &[(0, 10)]), vec![]);
+        assert_eq!(subtract(0, 10, &[(10, 20)]), vec![(0, 10)]);
+    }
+}
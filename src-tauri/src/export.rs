@@ -0,0 +1,459 @@
+use crate::db::{Database, ExportEvent};
+use crate::estimate;
+use crate::ledger::local_day_bounds;
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportContent {
+    Events,
+    ProjectTime, // Minutes per project per day
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+    Timesheet, // Projects down, days across, hours in each cell (project time only)
+}
+
+/// Where project time comes from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeSource {
+    #[default]
+    Entries, // The time ledger
+    Estimated,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportRequest {
+    pub content: ExportContent,
+    pub format: ExportFormat,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate, // Inclusive
+    #[serde(default)]
+    pub time_source: TimeSource,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExportOutput {
+    pub content: String,
+    pub row_count: usize,
+}
+
+/// One row of an events export
+#[derive(Debug, Serialize, Clone, PartialEq)]
+struct EventRow {
+    date: String,
+    start: String,
+    end: String,
+    duration_minutes: i64,
+    event_type: String,
+    title: String,
+    project: Option<String>,
+    organizer: Option<String>,
+    repository_path: Option<String>,
+    domain: Option<String>,
+    link: Option<String>,
+}
+
+/// One row of a project time export
+#[derive(Debug, Serialize, Clone, PartialEq)]
+struct ProjectTimeRow {
+    date: NaiveDate,
+    project: String,
+    minutes: i64,
+    notes: Option<String>,
+}
+
+const UNASSIGNED: &str = "Unassigned";
+
+/// Render an export for the requested date range. Nothing here touches the filesystem.
+pub fn export(db: &Database, request: &ExportRequest) -> rusqlite::Result<ExportOutput> {
+    if request.end_date < request.start_date {
+        return Err(rusqlite::Error::InvalidParameterName(
+            "end_date must not be before start_date".to_string(),
+        ));
+    }
+
+    match request.content {
+        ExportContent::Events => {
+            let rows: Vec<EventRow> = db
+                .get_export_events(
+                    local_day_bounds(request.start_date).0,
+                    local_day_bounds(request.end_date).1,
+                )?
+                .iter()
+                .map(event_row)
+                .collect();
+            let content = match request.format {
+                ExportFormat::Csv => to_csv(
+                    &[
+                        "date",
+                        "start",
+                        "end",
+                        "duration_minutes",
+                        "event_type",
+                        "title",
+                        "project",
+                        "organizer",
+                        "repository_path",
+                        "domain",
+                        "link",
+                    ],
+                    rows.iter().map(|row| {
+                        vec![
+                            row.date.clone(),
+                            row.start.clone(),
+                            row.end.clone(),
+                            row.duration_minutes.to_string(),
+                            row.event_type.clone(),
+                            row.title.clone(),
+                            row.project.clone().unwrap_or_default(),
+                            row.organizer.clone().unwrap_or_default(),
+                            row.repository_path.clone().unwrap_or_default(),
+                            row.domain.clone().unwrap_or_default(),
+                            row.link.clone().unwrap_or_default(),
+                        ]
+                    }),
+                ),
+                ExportFormat::JsonLines => to_json_lines(&rows)?,
+                ExportFormat::Timesheet => {
+                    return Err(rusqlite::Error::InvalidParameterName(
+                        "The timesheet layout is only available for project time".to_string(),
+                    ))
+                }
+            };
+            Ok(ExportOutput {
+                content,
+                row_count: rows.len(),
+            })
+        }
+        ExportContent::ProjectTime => {
+            let rows = project_time_rows(db, request)?;
+            let content = match request.format {
+                ExportFormat::Csv => to_csv(
+                    &["date", "project", "minutes", "hours", "notes"],
+                    rows.iter().map(|row| {
+                        vec![
+                            row.date.to_string(),
+                            row.project.clone(),
+                            row.minutes.to_string(),
+                            hours(row.minutes),
+                            row.notes.clone().unwrap_or_default(),
+                        ]
+                    }),
+                ),
+                ExportFormat::JsonLines => to_json_lines(&rows)?,
+                ExportFormat::Timesheet => timesheet(&rows, request.start_date, request.end_date),
+            };
+            Ok(ExportOutput {
+                content,
+                row_count: rows.len(),
+            })
+        }
+    }
+}
+
+fn event_row(export: &ExportEvent) -> EventRow {
+    let event = &export.event;
+    let local = |timestamp: i64| Local.timestamp_opt(timestamp, 0).single();
+    let start = local(event.start_date);
+
+    EventRow {
+        date: start
+            .map(|dt| dt.date_naive().to_string())
+            .unwrap_or_default(),
+        start: start.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
+        end: local(event.end_date)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+        duration_minutes: (event.end_date - event.start_date).max(0) / 60,
+        event_type: event.event_type.clone(),
+        title: event.title.clone(),
+        project: export.project_name.clone(),
+        organizer: export.organizer_name.clone(),
+        repository_path: event.repository_path.clone(),
+        domain: event.domain.clone(),
+        link: event.external_link.clone(),
+    }
+}
+
+fn project_time_rows(
+    db: &Database,
+    request: &ExportRequest,
+) -> rusqlite::Result<Vec<ProjectTimeRow>> {
+    let names: HashMap<i64, String> = db
+        .get_all_projects()?
+        .into_iter()
+        .filter_map(|project| Some((project.id?, project.name)))
+        .collect();
+    let name = |project_id: Option<i64>| {
+        project_id
+            .and_then(|id| names.get(&id).cloned())
+            .unwrap_or_else(|| UNASSIGNED.to_string())
+    };
+
+    // Keyed by (date, project name) so rows come out in a stable order
+    let mut rows: BTreeMap<(NaiveDate, String), ProjectTimeRow> = BTreeMap::new();
+    match request.time_source {
+        TimeSource::Entries => {
+            for entry in db.get_time_entries(request.start_date, request.end_date)? {
+                let project = name(Some(entry.project_id));
+                let row = rows
+                    .entry((entry.date, project.clone()))
+                    .or_insert_with(|| ProjectTimeRow {
+                        date: entry.date,
+                        project,
+                        minutes: 0,
+                        notes: None,
+                    });
+                row.minutes += entry.duration_minutes;
+                if let Some(note) = entry.note.filter(|note| !note.trim().is_empty()) {
+                    row.notes = Some(match row.notes.take() {
+                        Some(notes) => format!("{}; {}", notes, note),
+                        None => note,
+                    });
+                }
+            }
+        }
+        TimeSource::Estimated => {
+            for day in estimate::estimate_range(db, request.start_date, request.end_date)? {
+                for total in day.project_totals.iter().filter(|t| t.minutes > 0) {
+                    let project = name(total.project_id);
+                    rows.insert(
+                        (day.date, project.clone()),
+                        ProjectTimeRow {
+                            date: day.date,
+                            project,
+                            minutes: total.minutes,
+                            notes: None,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(rows.into_values().collect())
+}
+
+/// Grid of hours with a column per day, a total per project and a final totals row
+fn timesheet(rows: &[ProjectTimeRow], start: NaiveDate, end: NaiveDate) -> String {
+    let days: Vec<NaiveDate> = start.iter_days().take_while(|day| *day <= end).collect();
+    let projects: BTreeSet<&str> = rows.iter().map(|row| row.project.as_str()).collect();
+    let minutes = |project: Option<&str>, day: Option<NaiveDate>| -> i64 {
+        rows.iter()
+            .filter(|row| project.is_none_or(|p| row.project == p))
+            .filter(|row| day.is_none_or(|d| row.date == d))
+            .map(|row| row.minutes)
+            .sum()
+    };
+
+    let mut header = vec!["Project".to_string()];
+    header.extend(days.iter().map(|day| day.to_string()));
+    header.push("Total".to_string());
+
+    // The totals row can't be told apart from a project that happens to share its label
+    let mut total_label = "Total".to_string();
+    while projects.contains(total_label.as_str()) {
+        total_label.push_str(" (all projects)");
+    }
+
+    let mut lines = Vec::new();
+    for project in projects.iter().copied().map(Some).chain([None]) {
+        let mut line = vec![project.unwrap_or(&total_label).to_string()];
+        line.extend(days.iter().map(|day| hours(minutes(project, Some(*day)))));
+        line.push(hours(minutes(project, None)));
+        lines.push(line);
+    }
+
+    let header: Vec<&str> = header.iter().map(String::as_str).collect();
+    to_csv(&header, lines.into_iter())
+}
+
+fn hours(minutes: i64) -> String {
+    format!("{:.2}", minutes as f64 / 60.0)
+}
+
+fn to_csv(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut out = String::new();
+    let mut push_line = |fields: Vec<String>| {
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    };
+    push_line(header.iter().map(|h| h.to_string()).collect());
+    rows.for_each(push_line);
+    out
+}
+
+/// Quote a field when it contains a delimiter, quote or line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_json_lines<T: Serialize>(rows: &[T]) -> rusqlite::Result<String> {
+    let mut out = String::new();
+    for row in rows {
+        out.push_str(
+            &serde_json::to_string(row)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
+        );
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Event;
+
+    fn row(date: &str, project: &str, minutes: i64) -> ProjectTimeRow {
+        ProjectTimeRow {
+            date: date.parse().unwrap(),
+            project: project.to_string(),
+            minutes,
+            notes: None,
+        }
+    }
+
+    /// A day with an Acme meeting from 9:00 to 10:30, a declined meeting, and a page visit
+    /// on a domain that isn't a work domain
+    fn seeded_db(day: NaiveDate) -> Database {
+        let db = Database::new(":memory:".into()).unwrap();
+        db.init_schema().unwrap();
+        let acme = db.create_project("Acme", None).unwrap();
+        let nine = local_day_bounds(day).0 + 9 * 3600;
+        let event = |external_id: &str, event_type: &str, title: &str, start, end| Event {
+            id: None,
+            event_type: event_type.to_string(),
+            title: title.to_string(),
+            start_date: start,
+            end_date: end,
+            external_id: Some(external_id.to_string()),
+            external_link: None,
+            type_specific_data: None,
+            project_id: None,
+            organizer_id: None,
+            repository_path: None,
+            domain: None,
+            series_id: None,
+            assignment_source: Default::default(),
+            assignment_rule_id: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        let (planning, _) = db
+            .upsert_event(&event(
+                "planning",
+                "calendar",
+                "Planning, Q2",
+                nine,
+                nine + 5400,
+            ))
+            .unwrap();
+        db.assign_event_to_project(planning, Some(acme)).unwrap();
+        let mut declined = event("offsite", "calendar", "Offsite", nine, nine + 3600);
+        declined.type_specific_data =
+            Some(r#"{"is_all_day":false,"participant_status":"declined"}"#.to_string());
+        db.upsert_event(&declined).unwrap();
+        let mut visit = event(
+            "https://news.example.com/",
+            "browser_history",
+            "News",
+            nine,
+            nine,
+        );
+        visit.domain = Some("news.example.com".to_string());
+        db.upsert_event(&visit).unwrap();
+        db
+    }
+
+    fn request(content: ExportContent, day: NaiveDate) -> ExportRequest {
+        ExportRequest {
+            content,
+            format: ExportFormat::Csv,
+            start_date: day,
+            end_date: day,
+            time_source: TimeSource::Estimated,
+        }
+    }
+
+    #[test]
+    fn test_events_csv_export() {
+        let day: NaiveDate = "2024-03-04".parse().unwrap();
+        let db = seeded_db(day);
+
+        let output = export(&db, &request(ExportContent::Events, day)).unwrap();
+        assert_eq!(output.row_count, 1);
+        let lines: Vec<&str> = output.content.split("\r\n").collect();
+        assert_eq!(lines.len(), 3); // Header, the meeting, and the trailing line break
+        assert!(lines[0].starts_with("date,start,end,duration_minutes,"));
+        assert!(lines[1].starts_with("2024-03-04,"));
+        assert!(lines[1].contains(",90,calendar,\"Planning, Q2\",Acme,"));
+    }
+
+    #[test]
+    fn test_estimated_project_time_csv_export() {
+        let day: NaiveDate = "2024-03-04".parse().unwrap();
+        let db = seeded_db(day);
+
+        let output = export(&db, &request(ExportContent::ProjectTime, day)).unwrap();
+        assert_eq!(
+            output.content,
+            "date,project,minutes,hours,notes\r\n2024-03-04,Acme,90,1.50,\r\n"
+        );
+        assert_eq!(output.row_count, 1);
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_timesheet_layout() {
+        let rows = vec![
+            row("2024-03-04", "Apollo", 90),
+            row("2024-03-05", "Apollo", 30),
+            row("2024-03-05", "Zeus", 45),
+        ];
+        let sheet = timesheet(
+            &rows,
+            "2024-03-04".parse().unwrap(),
+            "2024-03-05".parse().unwrap(),
+        );
+        assert_eq!(
+            sheet,
+            "Project,2024-03-04,2024-03-05,Total\r\n\
+             Apollo,1.50,0.50,2.00\r\n\
+             Zeus,0.00,0.75,0.75\r\n\
+             Total,1.50,1.25,2.75\r\n"
+        );
+    }
+
+    #[test]
+    fn test_timesheet_totals_row_never_matches_a_project() {
+        let day: NaiveDate = "2024-03-04".parse().unwrap();
+        let sheet = timesheet(&[row("2024-03-04", "Total", 60)], day, day);
+        assert_eq!(
+            sheet,
+            "Project,2024-03-04,Total\r\n\
+             Total,1.00,1.00\r\n\
+             Total (all projects),1.00,1.00\r\n"
+        );
+    }
+}
@@ -340,8 +340,10 @@ fn walk_single_reflog(
             continue;
         }
 
-        // Extract ref name and commit info
-        let ref_name_extracted = extract_ref_name(message, &activity_type);
+        // Extract ref name and commit info. Entries in a branch's own reflog (e.g. commits)
+        // belong to that branch even when the message doesn't name it.
+        let ref_name_extracted =
+            extract_ref_name(message, &activity_type).or_else(|| branch_name(ref_name));
         let commit_hash = Some(entry.id_new().to_string());
 
         // Get commit message if available
@@ -399,6 +401,11 @@ fn extract_ref_name(message: &str, activity_type: &GitActivityType) -> Option<St
     }
 }
 
+/// Short branch name for local branch refs: "refs/heads/feature/x" → "feature/x"
+fn branch_name(ref_name: &str) -> Option<String> {
+    ref_name.strip_prefix("refs/heads/").map(|b| b.to_string())
+}
+
 fn format_activity_message(reflog_message: &str, commit_message: &str) -> String {
     // For commits, use just the commit message (no prefix needed)
     if reflog_message.starts_with("commit") && !commit_message.is_empty() {
@@ -482,6 +489,16 @@ mod tests {
         assert_eq!(ref_name, Some("feature-branch".to_string()));
     }
 
+    #[test]
+    fn test_branch_name() {
+        assert_eq!(
+            branch_name("refs/heads/feature/PROJ-1-x"),
+            Some("feature/PROJ-1-x".to_string())
+        );
+        assert_eq!(branch_name("HEAD"), None);
+        assert_eq!(branch_name("refs/remotes/origin/main"), None);
+    }
+
     #[test]
     fn test_parse_repository_path_github_https() {
         assert_eq!(
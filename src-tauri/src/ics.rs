@@ -0,0 +1,1026 @@
+use crate::calendar::{CalendarEvent, EventStatus, ParticipantStatus};
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Settings key for an .ics file or a directory of them
+pub const ICS_PATH_SETTING: &str = "ics_calendar_path";
+
+/// Settings key for my email addresses (comma separated), to find my response among attendees
+pub const MY_EMAILS_SETTING: &str = "calendar_my_emails";
+
+/// Stops expanding rules without COUNT or UNTIL that can never produce an occurrence
+const MAX_RECURRENCE_PERIODS: u32 = 100_000;
+
+/// Calendar events from an .ics file, or every .ics file under a directory, that overlap the
+/// range. Recurring events are expanded into one event per occurrence. A file that can't be read
+/// fails the whole fetch, since sync removes stored events the result doesn't contain.
+pub fn get_ics_events_range(
+    path: &Path,
+    my_emails: &[String],
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<CalendarEvent>, String> {
+    let (start, end) = parse_range(start_date, end_date)?;
+
+    let single_file = path.is_file();
+    let mut events = Vec::new();
+    for file in ics_files(path)? {
+        let text = std::fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read calendar file {}: {}", file.display(), e))?;
+        // Without X-WR-CALNAME, name the calendar after the file, or the folder for a
+        // directory with one file per event
+        let fallback_name = if single_file {
+            file.file_stem()
+        } else {
+            file.parent().and_then(|dir| dir.file_name())
+        }
+        .map(|name| name.to_string_lossy().to_string());
+
+        events.extend(parse_ics(
+            &text,
+            fallback_name.as_deref(),
+            my_emails,
+            start,
+            end,
+        ));
+    }
+
+    Ok(events)
+}
+
+/// Sync window bounds given as RFC 3339 strings, like the other calendar sources take
+pub fn parse_range(
+    start_date: &str,
+    end_date: &str,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let start = DateTime::parse_from_rfc3339(start_date)
+        .map_err(|e| format!("Invalid start date: {}", e))?
+        .with_timezone(&Utc);
+    let end = DateTime::parse_from_rfc3339(end_date)
+        .map_err(|e| format!("Invalid end date: {}", e))?
+        .with_timezone(&Utc);
+    Ok((start, end))
+}
+
+fn ics_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if !path.is_dir() {
+        return Err(format!("Calendar path not found: {}", path.display()));
+    }
+
+    let mut files = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            // file_type doesn't follow symlinks, so linked directories can't loop
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                dirs.push(entry_path);
+            } else if entry_path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+            {
+                files.push(entry_path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Parse the VEVENTs of one iCalendar document into events overlapping [start, end).
+/// `my_emails` identifies my ATTENDEE entry, whose PARTSTAT becomes the participant status.
+pub fn parse_ics(
+    text: &str,
+    fallback_name: Option<&str>,
+    my_emails: &[String],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<CalendarEvent> {
+    let calendar = parse_components(text);
+    let calendar_name = calendar
+        .name
+        .clone()
+        .or_else(|| fallback_name.map(str::to_string));
+
+    // Occurrences replaced by a separate VEVENT with a RECURRENCE-ID, keyed by UID and
+    // the original start
+    let overridden: HashSet<(String, i64)> = calendar
+        .events
+        .iter()
+        .filter_map(|event| {
+            let recurrence_id = event.get("RECURRENCE-ID").and_then(parse_time)?;
+            Some((event_uid(event), recurrence_id.timestamp()))
+        })
+        .collect();
+
+    let mut events = Vec::new();
+    for event in &calendar.events {
+        let Some(dtstart) = event.get("DTSTART").and_then(parse_time) else {
+            continue;
+        };
+        let uid = event_uid(event);
+        let length = event_length(event, dtstart);
+
+        // Each start comes with the original start of its occurrence, for recurring events
+        let starts: Vec<(NaiveDateTime, Option<i64>)> = match (
+            event.get("RECURRENCE-ID").and_then(parse_time),
+            event.get("RRULE").and_then(|p| parse_rrule(&p.value)),
+        ) {
+            (Some(recurrence_id), _) => vec![(dtstart.local, Some(recurrence_id.timestamp()))],
+            (None, Some(rule)) => {
+                let excluded: HashSet<i64> = event
+                    .all("EXDATE")
+                    .flat_map(|prop| {
+                        prop.value
+                            .split(',')
+                            .filter_map(|value| parse_time_value(value, prop.param("TZID")))
+                            .map(|time| time.timestamp())
+                            .collect::<Vec<_>>()
+                    })
+                    .collect();
+                expand(&rule, dtstart, end)
+                    .into_iter()
+                    .map(|local| (local, dtstart.zone.to_utc(local).timestamp()))
+                    .filter(|(_, original)| {
+                        !excluded.contains(original)
+                            && !overridden.contains(&(uid.clone(), *original))
+                    })
+                    .map(|(local, original)| (local, Some(original)))
+                    .collect()
+            }
+            (None, None) => vec![(dtstart.local, None)],
+        };
+
+        let (organizer, organizer_email) = event
+            .get("ORGANIZER")
+            .map(participant)
+            .map(|(name, email)| (name.or_else(|| email.clone()), email))
+            .unwrap_or_default();
+        let attendees: Vec<String> = event
+            .all("ATTENDEE")
+            .map(participant)
+            .filter_map(|(name, email)| name.or(email))
+            .collect();
+        let status = match event
+            .text("STATUS")
+            .map(|s| s.to_ascii_uppercase())
+            .as_deref()
+        {
+            Some("CANCELLED") => EventStatus::Cancelled,
+            Some("TENTATIVE") => EventStatus::Tentative,
+            _ => EventStatus::Confirmed,
+        };
+        let participant_status = event
+            .all("ATTENDEE")
+            .find(|prop| {
+                participant(prop).1.is_some_and(|email| {
+                    my_emails
+                        .iter()
+                        .any(|mine| mine.eq_ignore_ascii_case(&email))
+                })
+            })
+            .map(|prop| {
+                match prop
+                    .param("PARTSTAT")
+                    .map(str::to_ascii_uppercase)
+                    .as_deref()
+                {
+                    Some("ACCEPTED") => ParticipantStatus::Accepted,
+                    Some("TENTATIVE") => ParticipantStatus::Tentative,
+                    // Delegating hands the meeting to someone else
+                    Some("DECLINED") | Some("DELEGATED") => ParticipantStatus::Declined,
+                    _ => ParticipantStatus::Pending,
+                }
+            });
+
+        for (local_start, original_start) in starts {
+            let begin = dtstart.zone.to_utc(local_start);
+            let finish = dtstart.zone.to_utc(local_start + length).max(begin);
+            let overlaps = begin < end && (finish > start || begin >= start);
+            if !overlaps {
+                continue;
+            }
+
+            events.push(CalendarEvent {
+                event_id: match original_start {
+                    Some(original) => format!("{}/{}", uid, utc_stamp(original)),
+                    None => uid.clone(),
+                },
+                title: event.text("SUMMARY").unwrap_or_default(),
+                start_date: begin.to_rfc3339(),
+                end_date: finish.to_rfc3339(),
+                location: event.text("LOCATION"),
+                notes: event.text("DESCRIPTION"),
+                is_all_day: dtstart.is_date,
+                attendees: attendees.clone(),
+                organizer: organizer.clone(),
+                organizer_email: organizer_email.clone(),
+                calendar_name: calendar_name.clone(),
+                calendar_account: None,
+                status,
+                participant_status,
+                // Occurrences and their overrides all share the series' UID
+                series_id: original_start.map(|_| uid.clone()),
+            });
+        }
+    }
+
+    events
+}
+
+/// UID of an event, or a hash of its title and start when the producer left it out
+fn event_uid(event: &Component) -> String {
+    event.text("UID").unwrap_or_else(|| {
+        let summary = event.text("SUMMARY").unwrap_or_default();
+        let dtstart = event.get("DTSTART").map_or("", |p| p.value.as_str());
+        format!("{:x}", md5::compute(format!("{}|{}", summary, dtstart)))
+    })
+}
+
+fn event_length(event: &Component, dtstart: IcsTime) -> Duration {
+    if let Some(dtend) = event.get("DTEND").and_then(parse_time) {
+        // Wall-clock difference keeps recurring events the same length across DST changes
+        if dtend.zone == dtstart.zone {
+            dtend.local - dtstart.local
+        } else {
+            Duration::seconds(dtend.timestamp() - dtstart.timestamp())
+        }
+    } else if let Some(duration) = event.get("DURATION").and_then(|p| parse_duration(&p.value)) {
+        duration
+    } else if dtstart.is_date {
+        Duration::days(1)
+    } else {
+        Duration::zero()
+    }
+}
+
+/// Display name from CN and email from a mailto: value
+fn participant(prop: &Property) -> (Option<String>, Option<String>) {
+    let name = prop
+        .param("CN")
+        .map(|cn| cn.trim().to_string())
+        .filter(|cn| !cn.is_empty());
+    let value = prop.value.trim();
+    let email = value
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        .map(|_| value[7..].to_string())
+        .filter(|email| !email.is_empty());
+    (name, email)
+}
+
+fn utc_stamp(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// A content line such as `DTSTART;TZID=Europe/Paris:20240305T090000`
+#[derive(Debug, Clone)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Component {
+    properties: Vec<Property>,
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> {
+        self.properties.iter().filter(move |p| p.name == name)
+    }
+
+    /// Unescaped text value, if present and not blank
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name)
+            .map(|p| unescape(&p.value))
+            .filter(|text| !text.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Calendar {
+    name: Option<String>, // X-WR-CALNAME
+    events: Vec<Component>,
+}
+
+fn parse_components(text: &str) -> Calendar {
+    let mut calendar = Calendar::default();
+    let mut stack: Vec<String> = Vec::new();
+    let mut current: Option<Component> = None;
+
+    for line in unfold(text) {
+        let Some(prop) = parse_property(&line) else {
+            continue;
+        };
+        match prop.name.as_str() {
+            "BEGIN" => {
+                let kind = prop.value.trim().to_ascii_uppercase();
+                if kind == "VEVENT" {
+                    current = Some(Component::default());
+                }
+                stack.push(kind);
+            }
+            "END" => {
+                if stack.pop().as_deref() == Some("VEVENT") {
+                    calendar.events.extend(current.take());
+                }
+            }
+            // Properties of nested components such as VALARM are ignored
+            _ => match stack.last().map(String::as_str) {
+                Some("VEVENT") => {
+                    if let Some(event) = current.as_mut() {
+                        event.properties.push(prop);
+                    }
+                }
+                Some("VCALENDAR") if prop.name == "X-WR-CALNAME" => {
+                    calendar.name = Some(unescape(&prop.value)).filter(|n| !n.trim().is_empty());
+                }
+                _ => {}
+            },
+        }
+    }
+
+    calendar
+}
+
+/// Join folded lines (a line starting with a space or tab continues the previous one)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(rest) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // The value starts at the first colon outside a quoted parameter value
+    let mut in_quotes = false;
+    let mut value_start = None;
+    let mut param_starts = Vec::new();
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => param_starts.push(i),
+            ':' if !in_quotes => {
+                value_start = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let value_start = value_start?;
+
+    let name_end = param_starts.first().copied().unwrap_or(value_start);
+    let mut bounds = param_starts.clone();
+    bounds.push(value_start);
+    let params = bounds
+        .windows(2)
+        .filter_map(|w| {
+            let (key, value) = line[w[0] + 1..w[1]].split_once('=')?;
+            Some((
+                key.trim().to_ascii_uppercase(),
+                value.trim().trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+
+    Some(Property {
+        name: line[..name_end].trim().to_ascii_uppercase(),
+        params,
+        value: line[value_start + 1..].to_string(),
+    })
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    Floating, // No zone given, or one we don't know; read as local time
+}
+
+impl Zone {
+    fn from_tzid(tzid: &str) -> Zone {
+        // Some producers prefix the IANA name, e.g. /mozilla.org/20050126_1/Europe/Paris
+        tzid.parse::<Tz>()
+            .ok()
+            .or_else(|| {
+                tzid.match_indices('/')
+                    .find_map(|(i, _)| tzid[i + 1..].parse::<Tz>().ok())
+            })
+            .map_or(Zone::Floating, Zone::Named)
+    }
+
+    fn to_utc(self, local: NaiveDateTime) -> DateTime<Utc> {
+        match self {
+            Zone::Utc => local.and_utc(),
+            Zone::Named(tz) => resolve(&tz, local),
+            Zone::Floating => resolve(&Local, local),
+        }
+    }
+}
+
+/// Earliest instant for a wall-clock time; times in a DST gap move forward an hour
+fn resolve<Z: TimeZone>(zone: &Z, local: NaiveDateTime) -> DateTime<Utc> {
+    zone.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            zone.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map_or(local.and_utc(), |dt| dt.with_timezone(&Utc))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IcsTime {
+    local: NaiveDateTime, // Wall-clock time in `zone`
+    zone: Zone,
+    is_date: bool, // DATE value, i.e. an all-day event
+}
+
+impl IcsTime {
+    fn timestamp(self) -> i64 {
+        self.zone.to_utc(self.local).timestamp()
+    }
+}
+
+fn parse_time(prop: &Property) -> Option<IcsTime> {
+    parse_time_value(&prop.value, prop.param("TZID"))
+}
+
+fn parse_time_value(value: &str, tzid: Option<&str>) -> Option<IcsTime> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(IcsTime {
+            local: date.and_time(NaiveTime::MIN),
+            zone: Zone::Floating,
+            is_date: true,
+        });
+    }
+
+    let (value, is_utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    Some(IcsTime {
+        local: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+        zone: if is_utc {
+            Zone::Utc
+        } else {
+            tzid.map_or(Zone::Floating, Zone::from_tzid)
+        },
+        is_date: false,
+    })
+}
+
+/// Parse a duration such as `PT1H30M`, `P1D` or `-P1W`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let rest = rest.strip_prefix(['P', 'p'])?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c.to_ascii_uppercase() {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit @ ('W' | 'D' | 'H' | 'M' | 'S') => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    _ => Duration::seconds(n),
+                };
+            }
+            _ => return None,
+        }
+    }
+    Some(total * sign)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The RRULE parts we expand. BYSETPOS and the sub-daily parts (BYHOUR etc.) are ignored.
+#[derive(Debug, Clone, PartialEq)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<IcsTime>,
+    by_day: Vec<(Option<i32>, Weekday)>, // e.g. 2MO is (Some(2), Mon), -1FR is (Some(-1), Fri)
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+fn parse_rrule(value: &str) -> Option<RecurrenceRule> {
+    let mut rule = RecurrenceRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+        by_month: Vec::new(),
+    };
+    let mut frequency = None;
+
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        let list = || value.split(',').map(str::trim);
+        match key.trim().to_ascii_uppercase().as_str() {
+            // HOURLY and finer aren't meeting patterns; leave those events out
+            "FREQ" => {
+                frequency = match value.trim().to_ascii_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    "MONTHLY" => Some(Frequency::Monthly),
+                    "YEARLY" => Some(Frequency::Yearly),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => rule.interval = value.trim().parse().ok().filter(|n| *n > 0)?,
+            "COUNT" => rule.count = value.trim().parse().ok(),
+            "UNTIL" => rule.until = parse_time_value(value, None),
+            "BYDAY" => rule.by_day = list().filter_map(parse_weekday_num).collect(),
+            "BYMONTHDAY" => rule.by_month_day = list().filter_map(|v| v.parse().ok()).collect(),
+            "BYMONTH" => rule.by_month = list().filter_map(|v| v.parse().ok()).collect(),
+            _ => {}
+        }
+    }
+
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+fn parse_weekday_num(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let split = value.len().checked_sub(2)?;
+    let (ordinal, day) = (value.get(..split)?, value.get(split..)?);
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        Some(ordinal.trim_start_matches('+').parse().ok()?)
+    };
+    Some((ordinal, weekday))
+}
+
+/// Wall-clock start of every occurrence from DTSTART up to `window_end`. Occurrences before
+/// the window are included so COUNT is honoured.
+fn expand(
+    rule: &RecurrenceRule,
+    dtstart: IcsTime,
+    window_end: DateTime<Utc>,
+) -> Vec<NaiveDateTime> {
+    let first_date = dtstart.local.date();
+    let time = dtstart.local.time();
+    let mut occurrences = Vec::new();
+
+    for period in 0..MAX_RECURRENCE_PERIODS {
+        let Some(sets) = period_days(rule, first_date, period * rule.interval) else {
+            break;
+        };
+        let mut dates: Vec<NaiveDate> = sets
+            .iter()
+            .flat_map(|set| select_days(rule, set, first_date))
+            .filter(|date| rule.by_month.is_empty() || rule.by_month.contains(&date.month()))
+            .collect();
+        dates.sort();
+        dates.dedup();
+
+        for date in dates {
+            let local = date.and_time(time);
+            if local < dtstart.local {
+                continue;
+            }
+            let past_until = rule.until.is_some_and(|until| {
+                if until.is_date {
+                    date > until.local.date()
+                } else {
+                    dtstart.zone.to_utc(local) > until.zone.to_utc(until.local)
+                }
+            });
+            if past_until || dtstart.zone.to_utc(local) >= window_end {
+                return occurrences;
+            }
+            occurrences.push(local);
+            if rule
+                .count
+                .is_some_and(|count| occurrences.len() >= count as usize)
+            {
+                return occurrences;
+            }
+        }
+    }
+
+    occurrences
+}
+
+/// The days making up the `step`th period after the one containing `first`. Yearly rules
+/// with BYMONTH get one set per month so BYDAY ordinals count within the month.
+fn period_days(rule: &RecurrenceRule, first: NaiveDate, step: u32) -> Option<Vec<Vec<NaiveDate>>> {
+    let days_from = |start: NaiveDate, same_period: &dyn Fn(NaiveDate) -> bool| {
+        start
+            .iter_days()
+            .take_while(|d| same_period(*d))
+            .collect::<Vec<_>>()
+    };
+
+    Some(match rule.frequency {
+        Frequency::Daily => vec![vec![first.checked_add_days(Days::new(step as u64))?]],
+        Frequency::Weekly => {
+            let week = first
+                .week(Weekday::Mon)
+                .first_day()
+                .checked_add_days(Days::new(7 * step as u64))?;
+            vec![week.iter_days().take(7).collect()]
+        }
+        Frequency::Monthly => {
+            let month = first.with_day(1)?.checked_add_months(Months::new(step))?;
+            vec![days_from(month, &|d| d.month() == month.month())]
+        }
+        Frequency::Yearly => {
+            let year = first.year().checked_add(step as i32)?;
+            if rule.by_month.is_empty() {
+                let start = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                vec![days_from(start, &|d| d.year() == year)]
+            } else {
+                rule.by_month
+                    .iter()
+                    .filter_map(|month| NaiveDate::from_ymd_opt(year, *month, 1))
+                    .map(|start| days_from(start, &|d| d.month() == start.month()))
+                    .collect()
+            }
+        }
+    })
+}
+
+fn select_days(rule: &RecurrenceRule, set: &[NaiveDate], first: NaiveDate) -> Vec<NaiveDate> {
+    if rule.by_day.is_empty() && rule.by_month_day.is_empty() {
+        // Without BYDAY or BYMONTHDAY the start date's weekday or day of month repeats
+        return set
+            .iter()
+            .copied()
+            .filter(|date| match rule.frequency {
+                Frequency::Daily => true,
+                Frequency::Weekly => date.weekday() == first.weekday(),
+                Frequency::Monthly => date.day() == first.day(),
+                Frequency::Yearly => {
+                    date.day() == first.day()
+                        && (!rule.by_month.is_empty() || date.month() == first.month())
+                }
+            })
+            .collect();
+    }
+
+    set.iter()
+        .copied()
+        .filter(|date| {
+            rule.by_month_day.is_empty()
+                || rule
+                    .by_month_day
+                    .iter()
+                    .any(|n| month_day_matches(*date, *n))
+        })
+        .filter(|date| {
+            rule.by_day.is_empty()
+                || rule.by_day.iter().any(|(ordinal, weekday)| {
+                    weekday_matches(rule.frequency, set, *date, *ordinal, *weekday)
+                })
+        })
+        .collect()
+}
+
+/// BYMONTHDAY counts from the end of the month when negative (-1 is the last day)
+fn month_day_matches(date: NaiveDate, n: i32) -> bool {
+    let days_in_month = date
+        .with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day()) as i32;
+    let day = date.day() as i32;
+    if n > 0 {
+        day == n
+    } else {
+        day - days_in_month - 1 == n
+    }
+}
+
+/// BYDAY ordinals (2MO, -1FR) only apply within monthly and yearly periods
+fn weekday_matches(
+    frequency: Frequency,
+    set: &[NaiveDate],
+    date: NaiveDate,
+    ordinal: Option<i32>,
+    weekday: Weekday,
+) -> bool {
+    if date.weekday() != weekday {
+        return false;
+    }
+    let Some(n) = ordinal.filter(|_| matches!(frequency, Frequency::Monthly | Frequency::Yearly))
+    else {
+        return true;
+    };
+
+    let same_weekday: Vec<NaiveDate> = set
+        .iter()
+        .copied()
+        .filter(|d| d.weekday() == weekday)
+        .collect();
+    let position = same_weekday.iter().position(|d| *d == date).unwrap_or(0) as i32;
+    if n > 0 {
+        position + 1 == n
+    } else {
+        position - same_weekday.len() as i32 == n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn starts(events: &[CalendarEvent]) -> Vec<&str> {
+        events.iter().map(|e| e.start_date.as_str()).collect()
+    }
+
+    const WEEKLY: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+X-WR-CALNAME:Work\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+DTSTART;TZID=Europe/Paris:20240301T093000\r\n\
+DTEND;TZID=Europe/Paris:20240301T094500\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,FR;COUNT=6\r\n\
+EXDATE;TZID=Europe/Paris:20240304T093000\r\n\
+SUMMARY:Standup\\, daily\r\n\
+ORGANIZER;CN=\"Doe, Jane\":mailto:jane@example.com\r\n\
+ATTENDEE;CN=Bob;PARTSTAT=ACCEPTED:mailto:bob@example.com\r\n\
+ATTENDEE:mailto:carol@example.com\r\n\
+DESCRIPTION:Line one\\nLine two that is folded\r\n\
+\x20 across lines\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:Reminder\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+RECURRENCE-ID;TZID=Europe/Paris:20240308T093000\r\n\
+DTSTART;TZID=Europe/Paris:20240308T110000\r\n\
+DTEND;TZID=Europe/Paris:20240308T111500\r\n\
+SUMMARY:Standup (moved)\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:offsite@example.com\r\n\
+DTSTART;VALUE=DATE:20240305\r\n\
+DTEND;VALUE=DATE:20240307\r\n\
+SUMMARY:Offsite\r\n\
+STATUS:CANCELLED\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_weekly_series_with_exception_and_override() {
+        let events = parse_ics(
+            WEEKLY,
+            None,
+            &["BOB@example.com".to_string()],
+            utc("2024-03-01T00:00:00Z"),
+            utc("2024-04-01T00:00:00Z"),
+        );
+
+        // COUNT=6 covers Mar 1, 4, 8, 11, 15, 18; the 4th is excluded and the 8th moved.
+        // Paris is UTC+1 until Mar 31.
+        assert_eq!(events.len(), 6);
+        assert_eq!(
+            starts(&events[..5]),
+            vec![
+                "2024-03-01T08:30:00+00:00",
+                "2024-03-11T08:30:00+00:00",
+                "2024-03-15T08:30:00+00:00",
+                "2024-03-18T08:30:00+00:00",
+                "2024-03-08T10:00:00+00:00",
+            ]
+        );
+
+        let first = &events[0];
+        assert_eq!(first.event_id, "standup@example.com/20240301T083000Z");
+        assert_eq!(first.title, "Standup, daily");
+        assert_eq!(first.end_date, "2024-03-01T08:45:00+00:00");
+        assert_eq!(first.organizer.as_deref(), Some("Doe, Jane"));
+        assert_eq!(first.organizer_email.as_deref(), Some("jane@example.com"));
+        assert_eq!(first.attendees, vec!["Bob", "carol@example.com"]);
+        assert_eq!(
+            first.notes.as_deref(),
+            Some("Line one\nLine two that is folded across lines")
+        );
+        assert_eq!(first.calendar_name.as_deref(), Some("Work"));
+
+        // The override keeps the id of the occurrence it replaces
+        assert_eq!(events[4].event_id, "standup@example.com/20240308T083000Z");
+        assert_eq!(events[4].title, "Standup (moved)");
+        assert!(events[..5]
+            .iter()
+            .all(|e| e.series_id.as_deref() == Some("standup@example.com")));
+
+        assert_eq!(first.status, EventStatus::Confirmed);
+        assert_eq!(first.participant_status, Some(ParticipantStatus::Accepted));
+        assert_eq!(events[4].participant_status, None);
+
+        // Cancelled events are kept so sync can decide what to do with them
+        assert_eq!(events[5].title, "Offsite");
+        assert!(events[5].is_all_day);
+        assert_eq!(events[5].status, EventStatus::Cancelled);
+        assert_eq!(events[5].series_id, None);
+    }
+
+    #[test]
+    fn test_window_limits_unbounded_rules() {
+        let text = "BEGIN:VCALENDAR\n\
+BEGIN:VEVENT\n\
+UID:review\n\
+DTSTART:20230102T150000Z\n\
+DURATION:PT1H\n\
+RRULE:FREQ=MONTHLY;BYDAY=-1FR\n\
+SUMMARY:Monthly review\n\
+END:VEVENT\n\
+END:VCALENDAR\n";
+        let events = parse_ics(
+            text,
+            Some("team"),
+            &[],
+            utc("2024-01-15T00:00:00Z"),
+            utc("2024-04-01T00:00:00Z"),
+        );
+
+        // Last Friday of each month
+        assert_eq!(
+            starts(&events),
+            vec![
+                "2024-01-26T15:00:00+00:00",
+                "2024-02-23T15:00:00+00:00",
+                "2024-03-29T15:00:00+00:00",
+            ]
+        );
+        assert_eq!(events[0].end_date, "2024-01-26T16:00:00+00:00");
+        assert_eq!(events[0].calendar_name.as_deref(), Some("team"));
+    }
+
+    #[test]
+    fn test_rrule_variants() {
+        let dtstart = |s: &str| parse_time_value(s, Some("America/New_York")).unwrap();
+        let far = utc("2030-01-01T00:00:00Z");
+        let dates = |rule: &str, start: &str| -> Vec<String> {
+            expand(&parse_rrule(rule).unwrap(), dtstart(start), far)
+                .iter()
+                .map(|dt| dt.date().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            dates(
+                "FREQ=DAILY;INTERVAL=2;UNTIL=20240107T235959Z",
+                "20240101T090000"
+            ),
+            vec!["2024-01-01", "2024-01-03", "2024-01-05", "2024-01-07"]
+        );
+        // The 31st is skipped in months without one
+        assert_eq!(
+            dates("FREQ=MONTHLY;COUNT=3", "20240131T090000"),
+            vec!["2024-01-31", "2024-03-31", "2024-05-31"]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH;COUNT=2",
+                "20231123T120000"
+            ),
+            vec!["2023-11-23", "2024-11-28"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1;COUNT=2", "20240131T090000"),
+            vec!["2024-01-31", "2024-02-29"]
+        );
+        assert_eq!(parse_rrule("FREQ=HOURLY"), None);
+
+        // The wall-clock time holds across the March DST change
+        let rule = parse_rrule("FREQ=WEEKLY;COUNT=2").unwrap();
+        let start = dtstart("20240305T090000");
+        let utc_starts: Vec<_> = expand(&rule, start, far)
+            .into_iter()
+            .map(|local| start.zone.to_utc(local).to_rfc3339())
+            .collect();
+        assert_eq!(
+            utc_starts,
+            vec!["2024-03-05T14:00:00+00:00", "2024-03-12T13:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_and_zones() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("-P1DT2H"), Some(-Duration::hours(26)));
+        assert_eq!(parse_duration("1H"), None);
+
+        assert_eq!(
+            Zone::from_tzid("/mozilla.org/20050126_1/Europe/Paris"),
+            Zone::Named(chrono_tz::Europe::Paris)
+        );
+        assert_eq!(Zone::from_tzid("W. Europe Standard Time"), Zone::Floating);
+    }
+
+    #[test]
+    fn test_unreadable_file_fails_the_fetch() {
+        let dir = std::env::temp_dir().join(format!("traceback-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("work.ics"), WEEKLY).unwrap();
+        // Not UTF-8, so it can't be read as text
+        std::fs::write(dir.join("broken.ics"), [0xff, 0xfe, 0x00]).unwrap();
+
+        // An error rather than the readable file's events, so sync keeps what it has stored
+        let result =
+            get_ics_events_range(&dir, &[], "2024-03-01T00:00:00Z", "2024-04-01T00:00:00Z");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(result.unwrap_err().contains("broken.ics"));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::db::{Database, TimeEntry};
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Calendar span that a ledger or report covers
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Day,
+    Week, // ISO week, Monday to Sunday
+    Month,
+}
+
+impl Period {
+    /// First and last day (inclusive) of the period containing `date`
+    pub fn range(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Period::Day => (date, date),
+            Period::Week => {
+                let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+            Period::Month => {
+                let start = date.with_day(1).unwrap_or(date);
+                let end = start + Months::new(1) - Duration::days(1);
+                (start, end)
+            }
+        }
+    }
+
+    /// Range of the period immediately before the one containing `date`
+    pub fn previous_range(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let (start, _) = self.range(date);
+        self.range(start - Duration::days(1))
+    }
+}
+
+/// Start and end (exclusive) of a local calendar day as UTC seconds
+pub fn local_day_bounds(date: NaiveDate) -> (i64, i64) {
+    let midnight = |date: NaiveDate| {
+        let naive = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            // Midnight can fall in a DST gap; the first valid instant is an hour later
+            .or_else(|| {
+                Local
+                    .from_local_datetime(&(naive + Duration::hours(1)))
+                    .earliest()
+            })
+            .map_or(naive.and_utc().timestamp(), |dt| dt.timestamp())
+    };
+    (midnight(date), midnight(date + Duration::days(1)))
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TimeLedger {
+    pub period: Period,
+    pub start: NaiveDate,
+    pub end: NaiveDate, // Inclusive
+    pub entries: Vec<TimeEntry>,
+    pub project_totals: Vec<ProjectTotal>,
+    pub total_minutes: i64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ProjectTotal {
+    pub project_id: i64,
+    pub minutes: i64,
+}
+
+/// Time entries for the day, ISO week or month containing `date`, with per-project totals
+pub fn get_ledger(db: &Database, period: Period, date: NaiveDate) -> rusqlite::Result<TimeLedger> {
+    let (start, end) = period.range(date);
+    let entries = db.get_time_entries(start, end)?;
+
+    let mut totals: BTreeMap<i64, i64> = BTreeMap::new();
+    for entry in &entries {
+        *totals.entry(entry.project_id).or_default() += entry.duration_minutes;
+    }
+    let mut project_totals: Vec<ProjectTotal> = totals
+        .into_iter()
+        .map(|(project_id, minutes)| ProjectTotal {
+            project_id,
+            minutes,
+        })
+        .collect();
+    project_totals.sort_by_key(|total| std::cmp::Reverse(total.minutes));
+
+    Ok(TimeLedger {
+        period,
+        start,
+        end,
+        total_minutes: project_totals.iter().map(|t| t.minutes).sum(),
+        entries,
+        project_totals,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_period_ranges() {
+        // 2024-02-29 is a Thursday in a leap year
+        let day = date("2024-02-29");
+        assert_eq!(Period::Day.range(day), (day, day));
+        assert_eq!(
+            Period::Week.range(day),
+            (date("2024-02-26"), date("2024-03-03"))
+        );
+        assert_eq!(
+            Period::Month.range(day),
+            (date("2024-02-01"), date("2024-02-29"))
+        );
+    }
+
+    #[test]
+    fn test_previous_ranges() {
+        let day = date("2024-03-01");
+        assert_eq!(
+            Period::Month.previous_range(day),
+            (date("2024-02-01"), date("2024-02-29"))
+        );
+        assert_eq!(
+            Period::Week.previous_range(day),
+            (date("2024-02-19"), date("2024-02-25"))
+        );
+        assert_eq!(
+            Period::Day.previous_range(day),
+            (date("2024-02-29"), date("2024-02-29"))
+        );
+    }
+}
@@ -1,24 +1,47 @@
 mod browser;
+mod caldav;
 mod calendar;
 mod db;
+mod estimate;
+mod export;
 mod git;
+mod ics;
+mod ledger;
+mod privacy;
+mod reports;
+mod rules;
+mod suggestions;
 mod sync;
 mod sync_events;
+mod taxonomy;
 
-use browser::auto_detect_zen_profile;
+use browser::BrowserProfile;
+use caldav::{CalDavClient, CalDavConfig};
 use calendar::{check_calendar_permission, get_calendar_events_range, CalendarPermissionStatus};
-use chrono::{DateTime, Utc};
-use db::{Database, Event, Project, ProjectRule, SyncStatus};
+use chrono::{DateTime, NaiveDate, Utc};
+use db::{
+    Database, Event, NoteInput, Project, ProjectRule, RuleApplicationResult, SearchFilters,
+    SearchResult, SyncStatus,
+};
+use estimate::DayEstimate;
+use export::ExportRequest;
 use git::{discover_repositories, get_repository_activities};
-use std::path::PathBuf;
+use ledger::{Period, TimeLedger};
+use privacy::{BrowserFilter, BrowserFilterRule, FilterPreview};
+use reports::{PeriodReport, RecurringSeries};
+use rules::{AssignmentExplanation, RuleCondition, RuleEngine, RulePreview};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use suggestions::RuleSuggestion;
 
 // Default sync window for all event sources on initial sync
 const DEFAULT_SYNC_DAYS_BACK: i64 = 90;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use sync::{sync_git_activity, sync_single_event};
+use sync::{sync_git_activity, sync_single_event, CancelledEventHandling};
 use tauri::menu::{MenuBuilder, SubmenuBuilder};
 use tauri::{Emitter, Manager, State};
+use taxonomy::{ImportOptions, ImportReport, TaxonomyDocument, TaxonomyFormat};
 
 #[derive(Clone)]
 struct AppState {
@@ -203,10 +226,17 @@ fn sync_all_sources(state: State<AppState>, app: tauri::AppHandle) -> Result<(),
             )
             .await
             {
-                Ok((new, updated)) => {
+                Ok((new, updated, assigned, removed)) => {
                     total_new += new;
                     total_updated += updated;
-                    emit_source_completed(&app_clone, SyncSource::Calendar, new, updated);
+                    emit_source_completed(
+                        &app_clone,
+                        SyncSource::Calendar,
+                        new,
+                        updated,
+                        assigned,
+                        removed,
+                    );
                 }
                 Err(e) => {
                     check_cancelled!();
@@ -230,10 +260,10 @@ fn sync_all_sources(state: State<AppState>, app: tauri::AppHandle) -> Result<(),
                 sync_since_timestamp,
                 is_first_sync,
             ) {
-                Ok((new, updated)) => {
+                Ok((new, updated, assigned)) => {
                     total_new += new;
                     total_updated += updated;
-                    emit_source_completed(&app_clone, SyncSource::Git, new, updated);
+                    emit_source_completed(&app_clone, SyncSource::Git, new, updated, assigned, 0);
                 }
                 Err(e) => {
                     check_cancelled!();
@@ -251,16 +281,18 @@ fn sync_all_sources(state: State<AppState>, app: tauri::AppHandle) -> Result<(),
                 "Fetching browser history".to_string(),
             );
 
-            match sync_browser_source(
-                &state_clone,
-                &app_clone,
-                sync_since_timestamp,
-                is_first_sync,
-            ) {
-                Ok((new, updated)) => {
+            match sync_browser_source(&state_clone, &app_clone, now_timestamp) {
+                Ok((new, updated, assigned)) => {
                     total_new += new;
                     total_updated += updated;
-                    emit_source_completed(&app_clone, SyncSource::Browser, new, updated);
+                    emit_source_completed(
+                        &app_clone,
+                        SyncSource::Browser,
+                        new,
+                        updated,
+                        assigned,
+                        0,
+                    );
                 }
                 Err(e) => {
                     check_cancelled!();
@@ -284,16 +316,55 @@ fn sync_all_sources(state: State<AppState>, app: tauri::AppHandle) -> Result<(),
     Ok(())
 }
 
-/// Sync calendar events for a given time range
+/// Sync calendar events for a given time range, then remove stored events in the range that
+/// the calendars no longer have. Returns (new, updated, auto_assigned, removed).
 async fn sync_calendar_source(
     state: &AppState,
     app: &tauri::AppHandle,
     start_date_rfc3339: &str,
     end_date_rfc3339: &str,
-) -> Result<(usize, usize), String> {
+) -> Result<(usize, usize, usize, usize), String> {
     use sync_events::*;
 
-    let calendar_events = get_calendar_events_range(start_date_rfc3339, end_date_rfc3339).await?;
+    let (ics_path, my_emails) = state.with_db(|db| {
+        let ics_path = db.get_setting(ics::ICS_PATH_SETTING)?;
+        let my_emails = db.get_setting(ics::MY_EMAILS_SETTING)?.unwrap_or_default();
+        Ok((ics_path, my_emails))
+    })?;
+    let ics_path = ics_path.filter(|path| !path.is_empty());
+    let my_emails: Vec<String> = my_emails
+        .split(',')
+        .map(|email| email.trim().to_string())
+        .filter(|email| !email.is_empty())
+        .collect();
+
+    let mut calendar_events = match ics_path.as_deref() {
+        Some(path) => ics::get_ics_events_range(
+            Path::new(path),
+            &my_emails,
+            start_date_rfc3339,
+            end_date_rfc3339,
+        )?,
+        None => Vec::new(),
+    };
+    let caldav_events =
+        sync_caldav_calendars(state, app, &my_emails, start_date_rfc3339, end_date_rfc3339)?;
+    let has_other_sources = ics_path.is_some() || caldav_events.is_some();
+    calendar_events.extend(caldav_events.unwrap_or_default());
+
+    // Removing events is only safe when every source that could have stored them answered.
+    // Off macOS the system calendar never has.
+    let mut all_sources_fetched = true;
+    match get_calendar_events_range(start_date_rfc3339, end_date_rfc3339).await {
+        Ok(events) => calendar_events.extend(events),
+        // EventKit is unavailable off macOS, which is fine when another source is configured
+        Err(e) if has_other_sources => {
+            eprintln!("Skipping system calendar: {}", e);
+            all_sources_fetched = !cfg!(target_os = "macos");
+        }
+        Err(e) => return Err(e),
+    }
+    let (window_start, window_end) = ics::parse_range(start_date_rfc3339, end_date_rfc3339)?;
     emit_sync_progress(
         app,
         SyncSource::Calendar,
@@ -302,59 +373,124 @@ async fn sync_calendar_source(
     );
 
     state.with_db(|db| {
+        let rules = RuleEngine::load(db)?;
+        let cancelled = CancelledEventHandling::load(db)?;
         let mut new_count = 0;
         let mut updated_count = 0;
+        let mut assigned_count = 0;
         for cal_event in &calendar_events {
             if state.cancel_sync.load(Ordering::Relaxed) {
                 return Err(rusqlite::Error::ExecuteReturnedResults);
             }
 
-            let (is_new, _) = sync_single_event(db, cal_event).map_err(|e| {
-                rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(e)))
-            })?;
+            let Some((is_new, _, auto_assigned)) =
+                sync_single_event(db, cal_event, &rules, cancelled).map_err(|e| {
+                    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(e)))
+                })?
+            else {
+                continue;
+            };
             if is_new {
                 new_count += 1;
             } else {
                 updated_count += 1;
             }
+            if auto_assigned {
+                assigned_count += 1;
+            }
         }
-        Ok((new_count, updated_count))
+
+        let removed_count = if all_sources_fetched {
+            let current: HashSet<String> = calendar_events
+                .iter()
+                .map(|event| event.event_id.clone())
+                .collect();
+            db.delete_missing_calendar_events(
+                window_start.timestamp(),
+                window_end.timestamp(),
+                &current,
+            )?
+        } else {
+            0
+        };
+        Ok((new_count, updated_count, assigned_count, removed_count))
     })
 }
 
+/// Refresh the local copy of the CalDAV server's calendars and expand it for the range.
+/// Returns None when no server is configured.
+fn sync_caldav_calendars(
+    state: &AppState,
+    app: &tauri::AppHandle,
+    my_emails: &[String],
+    start_date_rfc3339: &str,
+    end_date_rfc3339: &str,
+) -> Result<Option<Vec<calendar::CalendarEvent>>, String> {
+    use sync_events::*;
+
+    let Some(config) = state.with_db(CalDavConfig::load)? else {
+        return Ok(None);
+    };
+    emit_sync_progress(
+        app,
+        SyncSource::Calendar,
+        ProgressStatus::InProgress,
+        format!("Checking CalDAV calendars at {}", config.url),
+    );
+
+    // The database isn't locked while talking to the server
+    let (cached_calendars, cached_resources) =
+        state.with_db(|db| Ok((db.get_caldav_calendars()?, db.get_caldav_resources()?)))?;
+    let changes = CalDavClient::new(&config).fetch_changes(&cached_calendars, &cached_resources)?;
+    let resources = state.with_db(|db| {
+        db.apply_caldav_changes(&changes)?;
+        db.get_caldav_resources()
+    })?;
+
+    caldav::events_in_range(
+        &config,
+        &changes.calendars,
+        &resources,
+        my_emails,
+        start_date_rfc3339,
+        end_date_rfc3339,
+    )
+    .map(Some)
+}
+
 /// Sync git events since a given timestamp
 fn sync_git_source(
     app_state: &AppState,
     app: &tauri::AppHandle,
     since_timestamp: i64,
     _is_first_sync: bool,
-) -> Result<(usize, usize), String> {
+) -> Result<(usize, usize, usize), String> {
     use sync_events::*;
     // Get dev folder from settings
     let dev_folder = match app_state.with_db(|db| db.get_setting("git_dev_folder")) {
         Ok(Some(folder)) => folder,
         Ok(None) => {
-            return Ok((0, 0));
+            return Ok((0, 0, 0));
         }
         Err(_) => {
-            return Ok((0, 0));
+            return Ok((0, 0, 0));
         }
     };
 
     let path = PathBuf::from(&dev_folder);
     if !path.exists() || !path.is_dir() {
-        return Ok((0, 0));
+        return Ok((0, 0, 0));
     }
 
     let repositories = match discover_repositories(&path, 2) {
         Ok(repos) => repos,
         Err(_) => {
-            return Ok((0, 0));
+            return Ok((0, 0, 0));
         }
     };
 
     if repositories.is_empty() {
-        return Ok((0, 0));
+        return Ok((0, 0, 0));
     }
 
     emit_sync_progress(
@@ -368,8 +504,10 @@ fn sync_git_source(
         .ok_or_else(|| "Invalid sync timestamp".to_string())?
         .to_rfc3339();
 
+    let rules = app_state.with_db(RuleEngine::load)?;
     let mut total_new = 0;
     let mut total_updated = 0;
+    let mut total_assigned = 0;
 
     for repo in repositories {
         if app_state.cancel_sync.load(Ordering::Relaxed) {
@@ -387,74 +525,115 @@ fn sync_git_source(
                     return Err(rusqlite::Error::ExecuteReturnedResults);
                 }
 
-                if let Ok((is_new, _)) = sync_git_activity(db, activity, &repo) {
+                if let Ok((is_new, _, auto_assigned)) =
+                    sync_git_activity(db, activity, &repo, &rules)
+                {
                     if is_new {
                         total_new += 1;
                     } else {
                         total_updated += 1;
                     }
+                    if auto_assigned {
+                        total_assigned += 1;
+                    }
                 }
             }
             Ok(())
         })?;
     }
 
-    Ok((total_new, total_updated))
+    Ok((total_new, total_updated, total_assigned))
 }
 
-/// Sync browser history since a given timestamp
+/// Sync browser history up to `until`. Each profile is read from where it was last synced, so
+/// a profile added or re-enabled later gets the default window of history first.
 fn sync_browser_source(
     app_state: &AppState,
     app: &tauri::AppHandle,
-    since_timestamp: i64,
-    _is_first_sync: bool,
-) -> Result<(usize, usize), String> {
+    until: i64,
+) -> Result<(usize, usize, usize), String> {
     use sync_events::*;
-    // Get profile path, discovered repos, and GitHub orgs
-    let (profile_path, discovered_repos, github_orgs) = match app_state.with_db(|db| {
-        let profile_path = match db.get_setting("zen_browser_profile_path")? {
-            Some(path) => path,
-            None => {
-                return Err(rusqlite::Error::QueryReturnedNoRows);
-            }
-        };
+    // Get profiles, privacy filter, discovered repos, and GitHub orgs
+    let (profiles, filter, discovered_repos, github_orgs, rules) = match app_state.with_db(|db| {
+        let default_since =
+            (Utc::now() - chrono::Duration::days(DEFAULT_SYNC_DAYS_BACK)).timestamp();
+        let profiles = browser::enabled_profiles(db)?
+            .into_iter()
+            .map(|profile| {
+                let since = db.get_browser_sync_cursor(&profile.path)?;
+                Ok((profile, since.unwrap_or(default_since)))
+            })
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if profiles.is_empty() {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
 
+        let filter = BrowserFilter::load(db)?;
         let discovered_repos = db.get_discovered_repository_paths()?;
         let github_orgs = db.get_github_orgs()?;
+        let rules = RuleEngine::load(db)?;
 
-        Ok((profile_path, discovered_repos, github_orgs))
+        Ok((profiles, filter, discovered_repos, github_orgs, rules))
     }) {
         Ok(data) => data,
-        Err(_) => return Ok((0, 0)),
+        Err(_) => return Ok((0, 0, 0)),
     };
 
-    let now = Utc::now();
-    let visits =
-        match browser::get_browser_visits_range(&profile_path, since_timestamp, now.timestamp()) {
-            Ok(visits) => {
-                emit_sync_progress(
-                    app,
-                    SyncSource::Browser,
-                    ProgressStatus::InProgress,
-                    format!("Processing {} browser visits", visits.len()),
+    // A profile that can't be read is skipped, unless none of them can
+    let mut visits = Vec::new();
+    let mut read_profiles = Vec::new();
+    let mut errors = Vec::new();
+    for (profile, since) in &profiles {
+        match browser::get_browser_visits_range(profile.browser, &profile.path, *since, until) {
+            Ok(found) => {
+                visits.extend(
+                    found
+                        .into_iter()
+                        .filter(|visit| filter.includes(visit))
+                        .map(|visit| (profile, visit)),
                 );
-                visits
-            }
-            Err(e) => {
-                return Err(e);
+                read_profiles.push(profile);
             }
-        };
+            Err(e) => errors.push(format!(
+                "{} profile '{}': {}",
+                profile.browser.display_name(),
+                profile.name,
+                e
+            )),
+        }
+    }
+    if errors.len() == profiles.len() {
+        return Err(errors.join("; "));
+    }
+    for error in &errors {
+        eprintln!("Skipping browser profile: {}", error);
+    }
+    emit_sync_progress(
+        app,
+        SyncSource::Browser,
+        ProgressStatus::InProgress,
+        format!("Processing {} browser visits", visits.len()),
+    );
 
     let mut new_count = 0;
     let mut updated_count = 0;
+    let mut assigned_count = 0;
 
-    for visit in &visits {
+    for (profile, visit) in &visits {
         if app_state.cancel_sync.load(Ordering::Relaxed) {
             return Err("Sync cancelled".to_string());
         }
 
-        if let Ok((is_new, _)) = app_state.with_db(|db| {
-            sync::sync_browser_visit(db, visit, &discovered_repos, &github_orgs).map_err(|e| {
+        if let Ok((is_new, _, auto_assigned)) = app_state.with_db(|db| {
+            sync::sync_browser_visit(
+                db,
+                visit,
+                profile.label.as_deref(),
+                &discovered_repos,
+                &github_orgs,
+                &rules,
+            )
+            .map_err(|e| {
                 rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(e)))
             })
         }) {
@@ -463,10 +642,20 @@ fn sync_browser_source(
             } else {
                 updated_count += 1;
             }
+            if auto_assigned {
+                assigned_count += 1;
+            }
         }
     }
 
-    Ok((new_count, updated_count))
+    // Only profiles that were read move forward; the others retry the same range next time
+    app_state.with_db(|db| {
+        read_profiles
+            .iter()
+            .try_for_each(|profile| db.set_browser_sync_cursor(&profile.path, until))
+    })?;
+
+    Ok((new_count, updated_count, assigned_count))
 }
 
 #[tauri::command]
@@ -535,8 +724,14 @@ fn get_project(state: State<AppState>, id: i64) -> Result<Option<Project>, Strin
     state.with_db(|db| db.get_project(id))
 }
 
+/// Settings the webview can write but never read back
+const WRITE_ONLY_SETTINGS: &[&str] = &[caldav::PASSWORD_SETTING];
+
 #[tauri::command]
 fn get_setting(state: State<AppState>, key: String) -> Result<Option<String>, String> {
+    if WRITE_ONLY_SETTINGS.contains(&key.as_str()) {
+        return Err(format!("The {} setting can't be read back", key));
+    }
     state.with_db(|db| db.get_setting(&key))
 }
 
@@ -583,6 +778,40 @@ fn update_project_rule(
     state.with_db(|db| db.update_project_rule(rule_id, project_id, &rule_type, &match_value))
 }
 
+/// Dry-run a rule before saving it. Pass `rule_id` when previewing an edit to an existing rule.
+#[tauri::command]
+fn preview_project_rule(
+    state: State<AppState>,
+    rule_id: Option<i64>,
+    project_id: i64,
+    rule_type: String,
+    match_value: String,
+) -> Result<RulePreview, String> {
+    state.with_db(|db| {
+        let priority = match rule_id {
+            Some(id) => db
+                .get_project_rules(None)?
+                .into_iter()
+                .find(|rule| rule.id == Some(id))
+                .map(|rule| rule.priority)
+                .ok_or(rusqlite::Error::QueryReturnedNoRows)?,
+            None => db.next_rule_priority(),
+        };
+        let condition = RuleCondition::from_rule(&rule_type, &match_value)
+            .map_err(rusqlite::Error::InvalidParameterName)?;
+        let candidate = ProjectRule {
+            id: rule_id,
+            project_id,
+            rule_type,
+            match_value,
+            condition: Some(condition),
+            priority,
+            created_at: 0,
+        };
+        rules::preview_rule(db, &candidate)
+    })
+}
+
 #[tauri::command]
 fn delete_project_rule(state: State<AppState>, rule_id: i64) -> Result<(), String> {
     state.with_db(|db| db.delete_project_rule(rule_id))
@@ -594,23 +823,298 @@ fn reorder_project_rules(state: State<AppState>, rule_ids: Vec<i64>) -> Result<(
 }
 
 #[tauri::command]
-fn apply_rules_to_events(state: State<AppState>) -> Result<usize, String> {
+fn apply_rules_to_events(state: State<AppState>) -> Result<RuleApplicationResult, String> {
     state.with_db(|db| db.apply_rules_to_events())
 }
 
+/// Explain which rules match an event and why it ended up in its project (or none)
+#[tauri::command]
+fn explain_event_assignment(
+    state: State<AppState>,
+    event_id: i64,
+) -> Result<AssignmentExplanation, String> {
+    state.with_db(|db| rules::explain_assignment(db, event_id))
+}
+
+/// Write projects and their rules to a JSON or TOML file (chosen by extension)
+#[tauri::command]
+fn export_projects(state: State<AppState>, path: String) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    let content = state
+        .with_db(|db| taxonomy::export_taxonomy(db)?.to_string(TaxonomyFormat::from_path(&path)))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Import projects and rules from a file written by `export_projects`
+#[tauri::command]
+fn import_projects(
+    state: State<AppState>,
+    path: String,
+    options: ImportOptions,
+) -> Result<ImportReport, String> {
+    let path = PathBuf::from(path);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    state.with_db(|db| {
+        let document = TaxonomyDocument::parse(&content, TaxonomyFormat::from_path(&path))?;
+        taxonomy::import_taxonomy(db, &document, &options)
+    })
+}
+
+/// Candidate rules mined from manual assignments, best first
+#[tauri::command]
+fn suggest_project_rules(state: State<AppState>) -> Result<Vec<RuleSuggestion>, String> {
+    state.with_db(suggestions::suggest_rules)
+}
+
+/// Write events or project time for a date range to `path` (chosen with the save dialog).
+/// Returns the number of rows written.
+#[tauri::command]
+fn export_data(
+    state: State<AppState>,
+    path: String,
+    request: ExportRequest,
+) -> Result<usize, String> {
+    let output = state.with_db(|db| export::export(db, &request))?;
+    std::fs::write(&path, output.content)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(output.row_count)
+}
+
+/// Ranked full-text search across events, with a snippet for each match
+#[tauri::command]
+fn search_events(
+    state: State<AppState>,
+    query: String,
+    filters: Option<SearchFilters>,
+) -> Result<Vec<SearchResult>, String> {
+    state.with_db(|db| db.search_events(&query, &filters.unwrap_or_default()))
+}
+
+#[tauri::command]
+fn create_note(state: State<AppState>, note: NoteInput) -> Result<i64, String> {
+    state.with_db(|db| db.create_note(&note))
+}
+
 #[tauri::command]
-fn get_zen_profile_path(state: State<AppState>) -> Result<Option<String>, String> {
-    state.with_db(|db| db.get_setting("zen_browser_profile_path"))
+fn update_note(state: State<AppState>, id: i64, note: NoteInput) -> Result<(), String> {
+    state.with_db(|db| db.update_note(id, &note))
 }
 
 #[tauri::command]
-fn set_zen_profile_path(state: State<AppState>, path: String) -> Result<(), String> {
-    state.with_db(|db| db.set_setting("zen_browser_profile_path", &path))
+fn delete_note(state: State<AppState>, id: i64) -> Result<(), String> {
+    state.with_db(|db| db.delete_note(id))
+}
+
+fn parse_date(value: &str, name: &str) -> Result<NaiveDate, String> {
+    value
+        .parse()
+        .map_err(|e| format!("Failed to parse {} (expected YYYY-MM-DD): {}", name, e))
+}
+
+#[tauri::command]
+fn create_time_entry(
+    state: State<AppState>,
+    date: String,
+    project_id: i64,
+    duration_minutes: i64,
+    note: Option<String>,
+    event_ids: Vec<i64>,
+) -> Result<i64, String> {
+    let date = parse_date(&date, "date")?;
+    state.with_db(|db| {
+        db.create_time_entry(
+            date,
+            project_id,
+            duration_minutes,
+            note.as_deref(),
+            &event_ids,
+        )
+    })
 }
 
 #[tauri::command]
-fn auto_detect_zen_profile_path() -> Result<Option<String>, String> {
-    auto_detect_zen_profile()
+fn update_time_entry(
+    state: State<AppState>,
+    id: i64,
+    date: String,
+    project_id: i64,
+    duration_minutes: i64,
+    note: Option<String>,
+    event_ids: Vec<i64>,
+) -> Result<(), String> {
+    let date = parse_date(&date, "date")?;
+    state.with_db(|db| {
+        db.update_time_entry(
+            id,
+            date,
+            project_id,
+            duration_minutes,
+            note.as_deref(),
+            &event_ids,
+        )
+    })
+}
+
+#[tauri::command]
+fn delete_time_entry(state: State<AppState>, id: i64) -> Result<(), String> {
+    state.with_db(|db| db.delete_time_entry(id))
+}
+
+/// Time entries for the day, week or month containing `date`
+#[tauri::command]
+fn get_time_ledger(
+    state: State<AppState>,
+    period: Period,
+    date: String,
+) -> Result<TimeLedger, String> {
+    let date = parse_date(&date, "date")?;
+    state.with_db(|db| ledger::get_ledger(db, period, date))
+}
+
+/// Totals per project, event type, repository and domain, with deltas against the previous period
+#[tauri::command]
+fn get_period_report(
+    state: State<AppState>,
+    period: Period,
+    date: String,
+) -> Result<PeriodReport, String> {
+    let date = parse_date(&date, "date")?;
+    state.with_db(|db| reports::period_report(db, period, date))
+}
+
+/// Recurring calendar series with their time per month, for occurrences from `start_date`
+/// to `end_date`
+#[tauri::command]
+fn get_recurring_series(
+    state: State<AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<RecurringSeries>, String> {
+    let start = parse_date(&start_date, "start_date")?;
+    let end = parse_date(&end_date, "end_date")?;
+    if end < start {
+        return Err("end_date must not be before start_date".to_string());
+    }
+    state.with_db(|db| reports::recurring_series(db, start, end))
+}
+
+/// Estimated time blocks and per-project totals for each day from `start_date` to `end_date`
+#[tauri::command]
+fn get_estimated_time(
+    state: State<AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<DayEstimate>, String> {
+    let start = parse_date(&start_date, "start_date")?;
+    let end = parse_date(&end_date, "end_date")?;
+    if end < start {
+        return Err("end_date must not be before start_date".to_string());
+    }
+    state.with_db(|db| estimate::estimate_range(db, start, end))
+}
+
+/// Check the saved CalDAV settings by listing the server's calendars
+#[tauri::command]
+fn test_caldav_connection(state: State<AppState>) -> Result<Vec<String>, String> {
+    let config = state
+        .with_db(CalDavConfig::load)?
+        .ok_or_else(|| "No CalDAV server configured".to_string())?;
+    let calendars = CalDavClient::new(&config).list_calendars()?;
+    Ok(calendars
+        .into_iter()
+        .map(|calendar| calendar.display_name.unwrap_or(calendar.href))
+        .collect())
+}
+
+/// Profiles of every supported browser found on this machine
+#[tauri::command]
+fn discover_browser_profiles() -> Result<Vec<BrowserProfile>, String> {
+    browser::discover_profiles()
+}
+
+#[tauri::command]
+fn get_browser_filter_rules(state: State<AppState>) -> Result<Vec<BrowserFilterRule>, String> {
+    state.with_db(|db| db.get_browser_filter_rules())
+}
+
+#[tauri::command]
+fn create_browser_filter_rule(
+    state: State<AppState>,
+    rule: BrowserFilterRule,
+) -> Result<i64, String> {
+    state.with_db(|db| db.create_browser_filter_rule(&rule))
+}
+
+#[tauri::command]
+fn update_browser_filter_rule(
+    state: State<AppState>,
+    id: i64,
+    rule: BrowserFilterRule,
+) -> Result<(), String> {
+    state.with_db(|db| db.update_browser_filter_rule(id, &rule))
+}
+
+#[tauri::command]
+fn delete_browser_filter_rule(state: State<AppState>, id: i64) -> Result<(), String> {
+    state.with_db(|db| db.delete_browser_filter_rule(id))
+}
+
+#[tauri::command]
+fn get_default_browser_filters() -> Vec<&'static str> {
+    privacy::DEFAULT_DENY_PATTERNS.to_vec()
+}
+
+/// What the proposed filter rules would include or exclude compared with the saved ones,
+/// over recent history from the enabled browser profiles
+#[tauri::command]
+fn preview_browser_filter(
+    state: State<AppState>,
+    rules: Vec<BrowserFilterRule>,
+) -> Result<FilterPreview, String> {
+    rules.iter().try_for_each(BrowserFilterRule::validate)?;
+    let (profiles, current) =
+        state.with_db(|db| Ok((browser::enabled_profiles(db)?, BrowserFilter::load(db)?)))?;
+
+    let now = Utc::now().timestamp();
+    let mut visits = Vec::new();
+    for profile in &profiles {
+        // Unreadable profiles just don't contribute to the sample
+        if let Ok(found) = browser::get_browser_visits_range(
+            profile.browser,
+            &profile.path,
+            now - privacy::PREVIEW_DAYS * 24 * 60 * 60,
+            now,
+        ) {
+            visits.extend(found);
+        }
+    }
+
+    Ok(privacy::preview(
+        &current,
+        &BrowserFilter::new(rules),
+        &privacy::preview_sample(visits),
+    ))
+}
+
+/// The browser profile in a directory picked by hand
+#[tauri::command]
+fn inspect_browser_profile(path: String) -> Result<BrowserProfile, String> {
+    browser::profile_at(Path::new(&path))
+}
+
+/// Configured profiles, with their enabled flags and labels
+#[tauri::command]
+fn get_browser_profiles(state: State<AppState>) -> Result<Vec<BrowserProfile>, String> {
+    state.with_db(browser::load_profiles)
+}
+
+#[tauri::command]
+fn set_browser_profiles(
+    state: State<AppState>,
+    profiles: Vec<BrowserProfile>,
+) -> Result<(), String> {
+    state.with_db(|db| browser::save_profiles(db, &profiles))
 }
 
 #[tauri::command]
@@ -712,12 +1216,37 @@ pub fn run() {
             create_project_rule,
             get_project_rules,
             update_project_rule,
+            preview_project_rule,
             delete_project_rule,
             reorder_project_rules,
             apply_rules_to_events,
-            get_zen_profile_path,
-            set_zen_profile_path,
-            auto_detect_zen_profile_path,
+            explain_event_assignment,
+            suggest_project_rules,
+            export_projects,
+            import_projects,
+            create_time_entry,
+            update_time_entry,
+            delete_time_entry,
+            get_time_ledger,
+            get_estimated_time,
+            get_period_report,
+            get_recurring_series,
+            search_events,
+            export_data,
+            create_note,
+            update_note,
+            delete_note,
+            test_caldav_connection,
+            discover_browser_profiles,
+            get_browser_filter_rules,
+            create_browser_filter_rule,
+            update_browser_filter_rule,
+            delete_browser_filter_rule,
+            get_default_browser_filters,
+            preview_browser_filter,
+            inspect_browser_profile,
+            get_browser_profiles,
+            set_browser_profiles,
             get_github_orgs,
             add_github_org,
             remove_github_org,
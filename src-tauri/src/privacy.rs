@@ -0,0 +1,435 @@
+use crate::browser::BrowserVisit;
+use crate::db::Database;
+use crate::rules::glob_matches;
+use crate::sync::extract_domain;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// URL globs that are never ingested unless an allow rule says otherwise: browser internals,
+/// local development, and pages that tend to carry credentials or private content
+pub const DEFAULT_DENY_PATTERNS: &[&str] = &[
+    // Browser internal pages
+    "chrome://*",
+    "about:*",
+    "moz-extension://*",
+    "chrome-extension://*",
+    "edge://*",
+    "brave://*",
+    "arc://*",
+    // Localhost and local development
+    "http://localhost*",
+    "https://localhost*",
+    "http://127.0.0.1*",
+    "https://127.0.0.1*",
+    "*.local/*",
+    // Authentication & OAuth flows
+    "*/auth/*",
+    "*/oauth/*",
+    "*/login*",
+    "*/signin*",
+    "*/sso/*",
+    "*/saml/*",
+    "*/authorize*",
+    "*/callback*",
+    // Tokens and credentials in URL params
+    "*access_token=*",
+    "*id_token=*",
+    "*refresh_token=*",
+    "*api_key=*",
+    "*apikey=*",
+    "*secret=*",
+    "*password=*",
+    "*session_id=*",
+    // Password & security pages
+    "*/password/*",
+    "*/security/*",
+    "*/2fa/*",
+    "*/mfa/*",
+    // Payment & checkout
+    "*/checkout*",
+    "*/payment*",
+    "*/billing*",
+    // Admin panels
+    "*/admin/*",
+    "*/wp-admin/*",
+    // Email clients (specific message URLs)
+    "*mail.google.com/mail/u/*/#*",
+    "*outlook.live.com/mail/*/inbox/id/*",
+];
+
+/// How far back `preview_sample` callers read history, and how many distinct pages they keep
+pub const PREVIEW_DAYS: i64 = 14;
+const PREVIEW_SAMPLE_SIZE: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    Allow, // Overrides deny rules and the default patterns
+    Deny,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterField {
+    Url,
+    Domain,
+    Title,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    Glob,
+    Regex,
+}
+
+/// User-editable allow/deny rule, stored in `browser_filter_rules`. Patterns ignore case.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrowserFilterRule {
+    pub id: Option<i64>,
+    pub action: FilterAction,
+    pub field: FilterField,
+    pub kind: PatternKind,
+    pub pattern: String,
+}
+
+impl BrowserFilterRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pattern.trim().is_empty() {
+            return Err("Filter pattern cannot be empty".to_string());
+        }
+        match self.kind {
+            PatternKind::Glob => Ok(()),
+            PatternKind::Regex => build_regex(&self.pattern)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid regex '{}': {}", self.pattern, e)),
+        }
+    }
+}
+
+/// Why a visit is kept or dropped
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterDecision {
+    Included, // Nothing matched
+    Allowed { rule_id: Option<i64> },
+    Denied { rule_id: Option<i64> },
+    DefaultExcluded { pattern: String },
+}
+
+impl FilterDecision {
+    pub fn is_included(&self) -> bool {
+        matches!(
+            self,
+            FilterDecision::Included | FilterDecision::Allowed { .. }
+        )
+    }
+}
+
+struct CompiledRule {
+    rule: BrowserFilterRule,
+    regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn matches(&self, value: Option<&str>) -> bool {
+        let Some(value) = value else {
+            return false;
+        };
+        match self.rule.kind {
+            PatternKind::Glob => {
+                glob_matches(&self.rule.pattern.to_lowercase(), &value.to_lowercase())
+            }
+            PatternKind::Regex => self
+                .regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(value)),
+        }
+    }
+}
+
+/// Decides which browser visits are ingested: allow rules first, then deny rules, then the
+/// default patterns
+#[derive(Default)]
+pub struct BrowserFilter {
+    rules: Vec<CompiledRule>,
+}
+
+impl BrowserFilter {
+    /// Rules with an invalid regex never match; saved rules are validated up front
+    pub fn new(rules: Vec<BrowserFilterRule>) -> Self {
+        BrowserFilter {
+            rules: rules
+                .into_iter()
+                .map(|rule| CompiledRule {
+                    regex: (rule.kind == PatternKind::Regex)
+                        .then(|| build_regex(&rule.pattern).ok())
+                        .flatten(),
+                    rule,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(db: &Database) -> rusqlite::Result<Self> {
+        Ok(BrowserFilter::new(db.get_browser_filter_rules()?))
+    }
+
+    pub fn decide(&self, visit: &BrowserVisit) -> FilterDecision {
+        let domain = extract_domain(&visit.url);
+        let first_match = |action: FilterAction| {
+            self.rules.iter().find(|compiled| {
+                let value = match compiled.rule.field {
+                    FilterField::Url => Some(visit.url.as_str()),
+                    FilterField::Domain => Some(domain.as_str()),
+                    FilterField::Title => visit.title.as_deref(),
+                };
+                compiled.rule.action == action && compiled.matches(value)
+            })
+        };
+
+        if let Some(compiled) = first_match(FilterAction::Allow) {
+            return FilterDecision::Allowed {
+                rule_id: compiled.rule.id,
+            };
+        }
+        if let Some(compiled) = first_match(FilterAction::Deny) {
+            return FilterDecision::Denied {
+                rule_id: compiled.rule.id,
+            };
+        }
+
+        let url = visit.url.to_lowercase();
+        match DEFAULT_DENY_PATTERNS
+            .iter()
+            .find(|pattern| glob_matches(pattern, &url))
+        {
+            Some(pattern) => FilterDecision::DefaultExcluded {
+                pattern: pattern.to_string(),
+            },
+            None => FilterDecision::Included,
+        }
+    }
+
+    pub fn includes(&self, visit: &BrowserVisit) -> bool {
+        self.decide(visit).is_included()
+    }
+}
+
+fn build_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FilterPreview {
+    pub sample_size: usize,
+    pub included_count: usize, // Sampled pages the proposed rules would ingest
+    pub changes: Vec<FilterPreviewChange>,
+}
+
+/// A sampled page whose outcome differs between the saved and the proposed rules
+#[derive(Debug, Serialize, Clone)]
+pub struct FilterPreviewChange {
+    pub visit: BrowserVisit,
+    pub before: FilterDecision,
+    pub after: FilterDecision,
+}
+
+/// The most recent visit of each distinct page, newest first, capped to a sample size
+pub fn preview_sample(mut visits: Vec<BrowserVisit>) -> Vec<BrowserVisit> {
+    visits.sort_by_key(|visit| std::cmp::Reverse(visit.visit_date));
+    let mut seen = HashSet::new();
+    visits.retain(|visit| seen.insert(visit.url.clone()));
+    visits.truncate(PREVIEW_SAMPLE_SIZE);
+    visits
+}
+
+/// Compare what the saved and proposed filters would ingest from a sample of history
+pub fn preview(
+    current: &BrowserFilter,
+    proposed: &BrowserFilter,
+    sample: &[BrowserVisit],
+) -> FilterPreview {
+    let mut included_count = 0;
+    let mut changes = Vec::new();
+
+    for visit in sample {
+        let before = current.decide(visit);
+        let after = proposed.decide(visit);
+        if after.is_included() {
+            included_count += 1;
+        }
+        if before.is_included() != after.is_included() {
+            changes.push(FilterPreviewChange {
+                visit: visit.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    FilterPreview {
+        sample_size: sample.len(),
+        included_count,
+        changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(url: &str, title: Option<&str>) -> BrowserVisit {
+        BrowserVisit {
+            url: url.to_string(),
+            title: title.map(str::to_string),
+            visit_date: 0,
+            visit_count: 1,
+        }
+    }
+
+    fn rule(
+        id: i64,
+        action: FilterAction,
+        field: FilterField,
+        kind: PatternKind,
+        pattern: &str,
+    ) -> BrowserFilterRule {
+        BrowserFilterRule {
+            id: Some(id),
+            action,
+            field,
+            kind,
+            pattern: pattern.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_default_patterns() {
+        let filter = BrowserFilter::default();
+        assert!(filter.includes(&visit("https://github.com/a/b/pull/1", None)));
+        assert!(!filter.includes(&visit("https://example.com/Login?next=/", None)));
+        assert!(!filter.includes(&visit("https://bank.example.com/payment/new", None)));
+        assert!(!filter.includes(&visit("chrome://settings", None)));
+        assert_eq!(
+            filter.decide(&visit("https://x.com/cb?access_token=abc", None)),
+            FilterDecision::DefaultExcluded {
+                pattern: "*access_token=*".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_allow_rules_override_deny_rules_and_defaults() {
+        use FilterAction::*;
+        let filter = BrowserFilter::new(vec![
+            rule(
+                1,
+                Deny,
+                FilterField::Domain,
+                PatternKind::Glob,
+                "*.bank.com",
+            ),
+            rule(
+                2,
+                Deny,
+                FilterField::Title,
+                PatternKind::Regex,
+                r"\bsalary\b",
+            ),
+            rule(
+                3,
+                Allow,
+                FilterField::Url,
+                PatternKind::Glob,
+                "https://github.com/*/security/*",
+            ),
+        ]);
+
+        assert_eq!(
+            filter.decide(&visit("https://my.bank.com/home", None)),
+            FilterDecision::Denied { rule_id: Some(1) }
+        );
+        assert_eq!(
+            filter.decide(&visit("https://docs.example.com/d/1", Some("Salary bands"))),
+            FilterDecision::Denied { rule_id: Some(2) }
+        );
+        assert_eq!(
+            filter.decide(&visit(
+                "https://github.com/acme/api/security/advisories",
+                None
+            )),
+            FilterDecision::Allowed { rule_id: Some(3) }
+        );
+        assert!(filter.includes(&visit("https://docs.example.com/d/2", Some("Roadmap"))));
+    }
+
+    #[test]
+    fn test_preview_reports_changed_outcomes() {
+        let sample = preview_sample(vec![
+            BrowserVisit {
+                visit_date: 1,
+                ..visit("https://news.example.com/a", None)
+            },
+            BrowserVisit {
+                visit_date: 3,
+                ..visit("https://news.example.com/a", None)
+            },
+            BrowserVisit {
+                visit_date: 2,
+                ..visit("https://app.example.com/billing/invoices", None)
+            },
+        ]);
+        assert_eq!(sample.len(), 2);
+        assert_eq!(sample[0].visit_date, 3);
+
+        let proposed = BrowserFilter::new(vec![
+            rule(
+                0,
+                FilterAction::Deny,
+                FilterField::Domain,
+                PatternKind::Glob,
+                "news.*",
+            ),
+            rule(
+                0,
+                FilterAction::Allow,
+                FilterField::Url,
+                PatternKind::Regex,
+                "^https://app\\.example\\.com/billing/",
+            ),
+        ]);
+        let result = preview(&BrowserFilter::default(), &proposed, &sample);
+
+        assert_eq!(result.sample_size, 2);
+        assert_eq!(result.included_count, 1);
+        let changes: Vec<_> = result
+            .changes
+            .iter()
+            .map(|c| (c.visit.url.as_str(), c.after.is_included()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("https://news.example.com/a", false),
+                ("https://app.example.com/billing/invoices", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let mut invalid = rule(
+            0,
+            FilterAction::Deny,
+            FilterField::Url,
+            PatternKind::Regex,
+            "(",
+        );
+        assert!(invalid.validate().is_err());
+        invalid.pattern = " ".to_string();
+        invalid.kind = PatternKind::Glob;
+        assert!(invalid.validate().is_err());
+    }
+}
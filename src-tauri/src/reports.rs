@@ -0,0 +1,296 @@
+use crate::db::{AggregateDimension, AggregateRow, Database, Event};
+use crate::ledger::{local_day_bounds, Period};
+use crate::rules::is_cancelled_or_declined;
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    pub event_count: i64,
+    pub duration_seconds: i64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AggregateEntry {
+    pub key: Option<String>,
+    pub project_id: Option<i64>,
+    pub current: Totals,
+    pub previous: Totals,
+    pub delta: Totals, // current - previous
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PeriodReport {
+    pub period: Period,
+    pub start: NaiveDate,
+    pub end: NaiveDate, // Inclusive
+    pub previous_start: NaiveDate,
+    pub previous_end: NaiveDate,
+    pub by_project: Vec<AggregateEntry>,
+    pub by_event_type: Vec<AggregateEntry>,
+    pub by_repository_path: Vec<AggregateEntry>,
+    pub by_domain: Vec<AggregateEntry>,
+}
+
+/// A recurring calendar event, with the time its occurrences took each month
+#[derive(Debug, Serialize, Clone)]
+pub struct RecurringSeries {
+    pub series_id: String,
+    pub title: String,           // Title of the latest occurrence
+    pub project_id: Option<i64>, // Project of the latest occurrence
+    pub totals: Totals,
+    pub months: Vec<SeriesMonth>, // Oldest first
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SeriesMonth {
+    pub month: String, // "YYYY-MM" in local time
+    pub totals: Totals,
+}
+
+/// Recurring series with occurrences between `start` and `end` (inclusive), most time first
+pub fn recurring_series(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> rusqlite::Result<Vec<RecurringSeries>> {
+    let events = db.get_series_events(local_day_bounds(start).0, local_day_bounds(end).1)?;
+    Ok(summarize_series(&events))
+}
+
+/// Group occurrences (sorted by start) by series. Cancelled and declined occurrences took
+/// no time, so they're left out.
+fn summarize_series(events: &[Event]) -> Vec<RecurringSeries> {
+    let mut series: Vec<RecurringSeries> = Vec::new();
+
+    for event in events.iter().filter(|e| !is_cancelled_or_declined(e)) {
+        let Some(series_id) = &event.series_id else {
+            continue;
+        };
+        let month = Local
+            .timestamp_opt(event.start_date, 0)
+            .single()
+            .map_or_else(String::new, |start| start.format("%Y-%m").to_string());
+        let duration_seconds = (event.end_date - event.start_date).max(0);
+
+        let entry = match series.iter().position(|s| &s.series_id == series_id) {
+            Some(index) => &mut series[index],
+            None => {
+                series.push(RecurringSeries {
+                    series_id: series_id.clone(),
+                    title: String::new(),
+                    project_id: None,
+                    totals: Totals::default(),
+                    months: Vec::new(),
+                });
+                series.last_mut().unwrap()
+            }
+        };
+        entry.title = event.title.clone();
+        entry.project_id = event.project_id;
+        entry.totals.event_count += 1;
+        entry.totals.duration_seconds += duration_seconds;
+
+        if entry.months.last().is_none_or(|last| last.month != month) {
+            entry.months.push(SeriesMonth {
+                month,
+                totals: Totals::default(),
+            });
+        }
+        if let Some(last) = entry.months.last_mut() {
+            last.totals.event_count += 1;
+            last.totals.duration_seconds += duration_seconds;
+        }
+    }
+
+    series.sort_by_key(|s| std::cmp::Reverse(s.totals.duration_seconds));
+    series
+}
+
+/// Totals for the day, ISO week or month containing `date`, compared with the period before
+pub fn period_report(
+    db: &Database,
+    period: Period,
+    date: NaiveDate,
+) -> rusqlite::Result<PeriodReport> {
+    let (start, end) = period.range(date);
+    let (previous_start, previous_end) = period.previous_range(date);
+
+    let aggregate = |dimension| -> rusqlite::Result<Vec<AggregateEntry>> {
+        let current = db.aggregate_events(
+            dimension,
+            local_day_bounds(start).0,
+            local_day_bounds(end).1,
+        )?;
+        let previous = db.aggregate_events(
+            dimension,
+            local_day_bounds(previous_start).0,
+            local_day_bounds(previous_end).1,
+        )?;
+        Ok(compare(current, previous))
+    };
+
+    Ok(PeriodReport {
+        period,
+        start,
+        end,
+        previous_start,
+        previous_end,
+        by_project: aggregate(AggregateDimension::Project)?,
+        by_event_type: aggregate(AggregateDimension::EventType)?,
+        by_repository_path: aggregate(AggregateDimension::RepositoryPath)?,
+        by_domain: aggregate(AggregateDimension::Domain)?,
+    })
+}
+
+/// Pair up rows by key. Keys only seen in the previous period are kept so drops show up.
+fn compare(current: Vec<AggregateRow>, previous: Vec<AggregateRow>) -> Vec<AggregateEntry> {
+    let totals = |row: &AggregateRow| Totals {
+        event_count: row.event_count,
+        duration_seconds: row.duration_seconds,
+    };
+    let same_group =
+        |a: &AggregateRow, b: &AggregateRow| a.key == b.key && a.project_id == b.project_id;
+
+    let mut entries: Vec<AggregateEntry> = current
+        .iter()
+        .map(|row| AggregateEntry {
+            key: row.key.clone(),
+            project_id: row.project_id,
+            current: totals(row),
+            previous: previous
+                .iter()
+                .find(|p| same_group(p, row))
+                .map(totals)
+                .unwrap_or_default(),
+            delta: Totals::default(),
+        })
+        .collect();
+    entries.extend(
+        previous
+            .iter()
+            .filter(|p| !current.iter().any(|row| same_group(row, p)))
+            .map(|row| AggregateEntry {
+                key: row.key.clone(),
+                project_id: row.project_id,
+                current: Totals::default(),
+                previous: totals(row),
+                delta: Totals::default(),
+            }),
+    );
+
+    for entry in &mut entries {
+        entry.delta = Totals {
+            event_count: entry.current.event_count - entry.previous.event_count,
+            duration_seconds: entry.current.duration_seconds - entry.previous.duration_seconds,
+        };
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(key: &str, event_count: i64, duration_seconds: i64) -> AggregateRow {
+        AggregateRow {
+            key: Some(key.to_string()),
+            project_id: None,
+            event_count,
+            duration_seconds,
+        }
+    }
+
+    fn occurrence(series_id: &str, start_date: i64, minutes: i64, data: &str) -> Event {
+        Event {
+            id: None,
+            event_type: "calendar".to_string(),
+            title: format!("{} meeting", series_id),
+            start_date,
+            end_date: start_date + minutes * 60,
+            external_id: None,
+            external_link: None,
+            type_specific_data: Some(data.to_string()),
+            project_id: None,
+            organizer_id: None,
+            repository_path: None,
+            domain: None,
+            series_id: Some(series_id.to_string()),
+            assignment_source: Default::default(),
+            assignment_rule_id: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_series_totals_per_month() {
+        let local = |month: u32, day: u32| {
+            Local
+                .with_ymd_and_hms(2025, month, day, 10, 0, 0)
+                .unwrap()
+                .timestamp()
+        };
+        let attended = r#"{"is_all_day":false}"#;
+        let declined = r#"{"is_all_day":false,"participant_status":"declined"}"#;
+        let series = summarize_series(&[
+            occurrence("standup", local(1, 6), 15, attended),
+            occurrence("retro", local(1, 10), 60, attended),
+            occurrence("standup", local(1, 13), 15, attended),
+            occurrence("standup", local(1, 20), 15, declined),
+            occurrence("standup", local(2, 3), 15, attended),
+        ]);
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].series_id, "retro");
+        let standup = &series[1];
+        assert_eq!(standup.totals.event_count, 3);
+        assert_eq!(
+            standup.months,
+            vec![
+                SeriesMonth {
+                    month: "2025-01".to_string(),
+                    totals: Totals {
+                        event_count: 2,
+                        duration_seconds: 1800
+                    },
+                },
+                SeriesMonth {
+                    month: "2025-02".to_string(),
+                    totals: Totals {
+                        event_count: 1,
+                        duration_seconds: 900
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_includes_keys_from_both_periods() {
+        let entries = compare(
+            vec![row("git", 10, 0), row("calendar", 3, 5400)],
+            vec![row("calendar", 5, 7200), row("browser_history", 40, 0)],
+        );
+
+        let deltas: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                (
+                    e.key.as_deref().unwrap(),
+                    e.delta.event_count,
+                    e.delta.duration_seconds,
+                )
+            })
+            .collect();
+        assert_eq!(
+            deltas,
+            vec![
+                ("git", 10, 0),
+                ("calendar", -2, -1800),
+                ("browser_history", -40, 0)
+            ]
+        );
+    }
+}
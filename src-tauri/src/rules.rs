@@ -0,0 +1,1055 @@
+use crate::db::{
+    AssignmentSource, BrowserHistoryEventData, CalendarEventData, Database, Event, GitEventData,
+    ProjectRule,
+};
+use chrono::{Datelike, Local, NaiveTime, TimeZone, Weekday};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Evaluates project rules against individual in-memory events
+pub struct RuleEngine {
+    rules: Vec<ProjectRule>, // Sorted by priority (lowest priority number first)
+}
+
+impl RuleEngine {
+    pub fn load(db: &Database) -> rusqlite::Result<Self> {
+        Ok(RuleEngine {
+            rules: db.get_project_rules(None)?,
+        })
+    }
+
+    /// First rule in priority order that matches the event
+    pub fn find_match(&self, event: &Event) -> Option<&ProjectRule> {
+        self.rules.iter().find(|rule| rule_matches(rule, event))
+    }
+
+    /// Dry-run a rule that hasn't been saved yet (or an edit to an existing one).
+    /// The candidate takes the place of the saved rule with the same id, at its own priority.
+    pub fn preview(&self, candidate: &ProjectRule, events: &[Event]) -> RulePreview {
+        let mut rules: Vec<&ProjectRule> = self
+            .rules
+            .iter()
+            .filter(|rule| candidate.id.is_none() || rule.id != candidate.id)
+            .collect();
+        let position = rules
+            .iter()
+            .position(|rule| rule.priority > candidate.priority)
+            .unwrap_or(rules.len());
+        rules.insert(position, candidate);
+
+        let mut matches = Vec::new();
+        let mut claimed_count = 0;
+
+        for event in events.iter().filter(|e| rule_matches(candidate, e)) {
+            let current_rule_id = self.find_match(event).and_then(|rule| rule.id);
+            let winner = rules
+                .iter()
+                .position(|rule| rule_matches(rule, event))
+                .unwrap_or(position);
+
+            let status = if event.assignment_source == AssignmentSource::Manual {
+                PreviewStatus::Pinned
+            } else if winner < position {
+                PreviewStatus::ClaimedByHigherPriority {
+                    rule_id: rules[winner].id,
+                    project_id: rules[winner].project_id,
+                }
+            } else {
+                claimed_count += 1;
+                PreviewStatus::Claimed
+            };
+
+            matches.push(RulePreviewMatch {
+                event: event.clone(),
+                current_rule_id,
+                status,
+            });
+        }
+
+        RulePreview {
+            match_count: matches.len(),
+            claimed_count,
+            matches,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RulePreview {
+    pub match_count: usize,   // Events the rule matches on its own
+    pub claimed_count: usize, // Events it would actually be assigned
+    pub matches: Vec<RulePreviewMatch>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RulePreviewMatch {
+    pub event: Event,
+    pub current_rule_id: Option<i64>, // Winning rule among the saved rules
+    pub status: PreviewStatus,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PreviewStatus {
+    Claimed,
+    ClaimedByHigherPriority {
+        rule_id: Option<i64>,
+        project_id: i64,
+    },
+    Pinned, // Manually assigned, so no rule applies
+}
+
+/// Preview a rule against every stored event
+pub fn preview_rule(db: &Database, candidate: &ProjectRule) -> rusqlite::Result<RulePreview> {
+    let engine = RuleEngine::load(db)?;
+    let events = db.get_all_events()?;
+    Ok(engine.preview(candidate, &events))
+}
+
+pub fn rule_matches(rule: &ProjectRule, event: &Event) -> bool {
+    rule.condition
+        .as_ref()
+        .is_some_and(|condition| condition.matches(event))
+}
+
+pub fn check_rule(rule: &ProjectRule, event: &Event) -> RuleCheck {
+    match &rule.condition {
+        Some(condition) => condition.check(event),
+        None => RuleCheck::MissingCondition,
+    }
+}
+
+/// Structured rule condition, stored as JSON in `project_rules.condition`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    All {
+        conditions: Vec<RuleCondition>,
+    },
+    Any {
+        conditions: Vec<RuleCondition>,
+    },
+    Not {
+        condition: Box<RuleCondition>,
+    },
+    EventType {
+        event_types: Vec<String>,
+    },
+    Field {
+        field: RuleField,
+        matcher: TextMatcher,
+    },
+    AllDay,
+    AttendeeCount {
+        min: usize,
+    },
+    /// Event start in the user's local time zone. `after` is inclusive and `before` exclusive;
+    /// a window with `after` later than `before` wraps past midnight.
+    LocalTime {
+        #[serde(default)]
+        weekdays: Vec<Weekday>,
+        after: Option<NaiveTime>,
+        before: Option<NaiveTime>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleField {
+    Title,
+    Organizer,
+    Url,
+    Domain,
+    RepositoryPath,
+    Attendee, // Matches if any attendee matches
+    Calendar,
+    CalendarAccount,
+    Branch,
+    CommitMessage,
+    ActivityType,
+    SeriesId, // Recurring series shared by every occurrence
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TextMatcher {
+    Equals {
+        value: String,
+    },
+    Like {
+        pattern: String,
+    },
+    Glob {
+        pattern: String,
+    },
+    Regex {
+        pattern: String,
+        #[serde(default)]
+        case_insensitive: bool,
+        #[serde(skip)]
+        compiled: CompiledRegex,
+    },
+}
+
+/// Lazily compiled regex so each loaded rule compiles its pattern at most once
+#[derive(Debug, Clone, Default)]
+pub struct CompiledRegex(OnceLock<Option<Regex>>);
+
+impl PartialEq for CompiledRegex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl RuleCondition {
+    /// Build the condition for a rule as the commands receive it. Legacy rule types map to
+    /// their equivalent trees; "condition" rules carry the JSON tree in `match_value`.
+    pub fn from_rule(rule_type: &str, match_value: &str) -> Result<Self, String> {
+        let value = match_value.to_string();
+        let field =
+            |field: RuleField, matcher: TextMatcher| RuleCondition::Field { field, matcher };
+        let (event_types, condition): (&[&str], RuleCondition) = match rule_type {
+            "condition" => {
+                let condition: RuleCondition = serde_json::from_str(match_value)
+                    .map_err(|e| format!("Invalid rule condition: {}", e))?;
+                condition.validate()?;
+                return Ok(condition);
+            }
+            "organizer" => (
+                &["calendar"],
+                field(RuleField::Organizer, TextMatcher::Equals { value }),
+            ),
+            "title_pattern" => (
+                &["calendar"],
+                field(
+                    RuleField::Title,
+                    TextMatcher::Like {
+                        pattern: format!("%{}%", value),
+                    },
+                ),
+            ),
+            "attendee" => (
+                &["calendar"],
+                field(RuleField::Attendee, TextMatcher::Equals { value }),
+            ),
+            "attendee_count" => (
+                &["calendar"],
+                RuleCondition::AttendeeCount {
+                    min: match_value.trim().parse().map_err(|_| {
+                        format!("Attendee count must be a whole number, got '{}'", value)
+                    })?,
+                },
+            ),
+            "calendar" => (
+                &["calendar"],
+                field(RuleField::Calendar, TextMatcher::Equals { value }),
+            ),
+            "recurring_series" => (
+                &["calendar"],
+                field(RuleField::SeriesId, TextMatcher::Equals { value }),
+            ),
+            "branch_pattern" => (
+                &["git"],
+                field(RuleField::Branch, TextMatcher::Glob { pattern: value }),
+            ),
+            "ticket_prefix" => (
+                &["git"],
+                field(
+                    RuleField::CommitMessage,
+                    TextMatcher::Regex {
+                        pattern: format!(r"\b{}-\d+\b", regex::escape(match_value.trim())),
+                        case_insensitive: true,
+                        compiled: CompiledRegex::default(),
+                    },
+                ),
+            ),
+            "git_activity" => (
+                &["git"],
+                field(RuleField::ActivityType, TextMatcher::Equals { value }),
+            ),
+            "repository" => (
+                &["git", "browser_history"],
+                field(RuleField::RepositoryPath, TextMatcher::Equals { value }),
+            ),
+            "url_pattern" => (
+                &["browser_history"],
+                field(RuleField::Url, TextMatcher::Like { pattern: value }),
+            ),
+            "domain" => (
+                &["browser_history"],
+                field(RuleField::Domain, TextMatcher::Equals { value }),
+            ),
+            _ => return Err(format!("Unknown rule type: '{}'", rule_type)),
+        };
+
+        Ok(RuleCondition::All {
+            conditions: vec![
+                RuleCondition::EventType {
+                    event_types: event_types.iter().map(|t| t.to_string()).collect(),
+                },
+                condition,
+            ],
+        })
+    }
+
+    /// Reject conditions that could never be evaluated, such as malformed regexes
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            RuleCondition::All { conditions } | RuleCondition::Any { conditions } => {
+                conditions.iter().try_for_each(|c| c.validate())
+            }
+            RuleCondition::Not { condition } => condition.validate(),
+            RuleCondition::Field {
+                matcher:
+                    TextMatcher::Regex {
+                        pattern,
+                        case_insensitive,
+                        ..
+                    },
+                ..
+            } => build_regex(pattern, *case_insensitive)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid regex '{}': {}", pattern, e)),
+            RuleCondition::LocalTime {
+                weekdays,
+                after,
+                before,
+            } => {
+                if weekdays.is_empty() && after.is_none() && before.is_none() {
+                    Err("Local time condition needs weekdays or a time window".to_string())
+                } else if after.is_some() && after == before {
+                    Err("Local time window is empty".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            RuleCondition::All { conditions } => conditions.iter().all(|c| c.matches(event)),
+            RuleCondition::Any { conditions } => conditions.iter().any(|c| c.matches(event)),
+            RuleCondition::Not { condition } => !condition.matches(event),
+            RuleCondition::EventType { event_types } => event_types.contains(&event.event_type),
+            RuleCondition::Field { field, matcher } => field_values(*field, event)
+                .iter()
+                .any(|value| matcher.is_match(value)),
+            RuleCondition::AllDay => is_all_day(event),
+            RuleCondition::AttendeeCount { min } => attendee_count(event) >= *min,
+            RuleCondition::LocalTime {
+                weekdays,
+                after,
+                before,
+            } => local_time_matches(event, weekdays, *after, *before),
+        }
+    }
+
+    /// Like `matches`, but records why the condition did or did not hold
+    pub fn check(&self, event: &Event) -> RuleCheck {
+        match self {
+            RuleCondition::All { conditions } => {
+                let mut last = RuleCheck::Matched {
+                    field: "all",
+                    value: String::new(),
+                };
+                for condition in conditions {
+                    last = condition.check(event);
+                    if !last.is_match() {
+                        break;
+                    }
+                }
+                last
+            }
+            RuleCondition::Any { conditions } => {
+                let mut checks = Vec::new();
+                for condition in conditions {
+                    let check = condition.check(event);
+                    if check.is_match() {
+                        return check;
+                    }
+                    checks.push(check);
+                }
+                RuleCheck::NoAlternativeMatched { checks }
+            }
+            RuleCondition::Not { condition } => RuleCheck::Not {
+                inner: Box::new(condition.check(event)),
+            },
+            RuleCondition::EventType { event_types } => {
+                if event_types.contains(&event.event_type) {
+                    RuleCheck::Matched {
+                        field: "event_type",
+                        value: event.event_type.clone(),
+                    }
+                } else {
+                    RuleCheck::WrongEventType {
+                        expected: event_types.clone(),
+                        actual: event.event_type.clone(),
+                    }
+                }
+            }
+            RuleCondition::Field { field, matcher } => {
+                let values = field_values(*field, event);
+                if let Some(value) = values.iter().find(|value| matcher.is_match(value)) {
+                    return RuleCheck::Matched {
+                        field: field.as_str(),
+                        value: value.clone(),
+                    };
+                }
+
+                let value = (!values.is_empty()).then(|| values.join(", "));
+                match matcher {
+                    TextMatcher::Equals { .. } => RuleCheck::ValueMismatch {
+                        field: field.as_str(),
+                        value,
+                    },
+                    TextMatcher::Like { pattern }
+                    | TextMatcher::Glob { pattern }
+                    | TextMatcher::Regex { pattern, .. } => RuleCheck::PatternMismatch {
+                        field: field.as_str(),
+                        pattern: pattern.clone(),
+                        value,
+                    },
+                }
+            }
+            RuleCondition::AllDay => {
+                let all_day = is_all_day(event);
+                let value = all_day.to_string();
+                if all_day {
+                    RuleCheck::Matched {
+                        field: "is_all_day",
+                        value,
+                    }
+                } else {
+                    RuleCheck::ValueMismatch {
+                        field: "is_all_day",
+                        value: Some(value),
+                    }
+                }
+            }
+            RuleCondition::AttendeeCount { min } => {
+                let count = attendee_count(event);
+                if count >= *min {
+                    RuleCheck::Matched {
+                        field: "attendee_count",
+                        value: count.to_string(),
+                    }
+                } else {
+                    RuleCheck::ValueMismatch {
+                        field: "attendee_count",
+                        value: Some(count.to_string()),
+                    }
+                }
+            }
+            RuleCondition::LocalTime {
+                weekdays,
+                after,
+                before,
+            } => {
+                let value = local_start(event).map(|start| start.format("%a %H:%M").to_string());
+                if local_time_matches(event, weekdays, *after, *before) {
+                    RuleCheck::Matched {
+                        field: "local_time",
+                        value: value.unwrap_or_default(),
+                    }
+                } else {
+                    RuleCheck::ValueMismatch {
+                        field: "local_time",
+                        value,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl RuleField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleField::Title => "title",
+            RuleField::Organizer => "organizer",
+            RuleField::Url => "url",
+            RuleField::Domain => "domain",
+            RuleField::RepositoryPath => "repository_path",
+            RuleField::Attendee => "attendee",
+            RuleField::Calendar => "calendar",
+            RuleField::CalendarAccount => "calendar_account",
+            RuleField::Branch => "branch",
+            RuleField::CommitMessage => "commit_message",
+            RuleField::ActivityType => "activity_type",
+            RuleField::SeriesId => "series_id",
+        }
+    }
+}
+
+impl TextMatcher {
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            TextMatcher::Equals { value: expected } => value == expected,
+            TextMatcher::Like { pattern } => like_matches(pattern, value),
+            TextMatcher::Glob { pattern } => glob_matches(pattern, value),
+            TextMatcher::Regex {
+                pattern,
+                case_insensitive,
+                compiled,
+            } => compiled
+                .0
+                .get_or_init(|| build_regex(pattern, *case_insensitive).ok())
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(value)),
+        }
+    }
+}
+
+fn build_regex(pattern: &str, case_insensitive: bool) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+}
+
+/// Values of a field on an event; multi-valued fields (attendees) yield one entry per value
+pub(crate) fn field_values(field: RuleField, event: &Event) -> Vec<String> {
+    match field {
+        RuleField::Title => vec![event.title.clone()],
+        RuleField::Organizer => calendar_data(event)
+            .and_then(|data| data.organizer)
+            .into_iter()
+            .collect(),
+        RuleField::Url => browser_data(event)
+            .map(|data| data.url)
+            .into_iter()
+            .collect(),
+        RuleField::Domain => event.domain.clone().into_iter().collect(),
+        RuleField::RepositoryPath => event.repository_path.clone().into_iter().collect(),
+        RuleField::Attendee => calendar_data(event)
+            .and_then(|data| data.attendees)
+            .unwrap_or_default(),
+        RuleField::Calendar => calendar_data(event)
+            .and_then(|data| data.calendar_name)
+            .into_iter()
+            .collect(),
+        RuleField::CalendarAccount => calendar_data(event)
+            .and_then(|data| data.calendar_account)
+            .into_iter()
+            .collect(),
+        RuleField::Branch => git_data(event)
+            .and_then(|data| data.ref_name)
+            .into_iter()
+            .collect(),
+        RuleField::CommitMessage => git_data(event)
+            .filter(|data| data.activity_type == "commit")
+            .map(|_| event.title.clone())
+            .into_iter()
+            .collect(),
+        RuleField::ActivityType => git_data(event)
+            .map(|data| data.activity_type)
+            .into_iter()
+            .collect(),
+        RuleField::SeriesId => event.series_id.clone().into_iter().collect(),
+    }
+}
+
+fn attendee_count(event: &Event) -> usize {
+    calendar_data(event)
+        .and_then(|data| data.attendees)
+        .map_or(0, |attendees| attendees.len())
+}
+
+pub(crate) fn is_all_day(event: &Event) -> bool {
+    event.event_type == "calendar" && calendar_data(event).is_some_and(|data| data.is_all_day)
+}
+
+/// Calendar events kept by the "mark" setting even though I won't be attending
+pub(crate) fn is_cancelled_or_declined(event: &Event) -> bool {
+    event.event_type == "calendar"
+        && calendar_data(event).is_some_and(|data| {
+            crate::calendar::is_cancelled_or_declined(data.status, data.participant_status)
+        })
+}
+
+/// Why a rule did or did not match an event
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCheck {
+    Matched {
+        field: &'static str,
+        value: String,
+    },
+    WrongEventType {
+        expected: Vec<String>,
+        actual: String,
+    },
+    PatternMismatch {
+        field: &'static str,
+        pattern: String,
+        value: Option<String>,
+    },
+    ValueMismatch {
+        field: &'static str,
+        value: Option<String>,
+    },
+    NoAlternativeMatched {
+        checks: Vec<RuleCheck>,
+    },
+    Not {
+        inner: Box<RuleCheck>, // Holds when the inner check fails
+    },
+    MissingCondition,
+}
+
+impl RuleCheck {
+    pub fn is_match(&self) -> bool {
+        match self {
+            RuleCheck::Matched { .. } => true,
+            RuleCheck::Not { inner } => !inner.is_match(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AssignmentExplanation {
+    pub event_id: i64,
+    pub project_id: Option<i64>,
+    pub assignment_source: AssignmentSource,
+    pub assignment_rule_id: Option<i64>,
+    pub winning_rule_id: Option<i64>, // First matching rule, even if a manual assignment overrides it
+    pub rules: Vec<RuleExplanation>,  // Every rule, in priority order
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RuleExplanation {
+    pub rule: ProjectRule,
+    pub matched: bool,
+    pub check: RuleCheck,
+}
+
+/// Walk every rule in priority order and record how each one treats the event
+pub fn explain_assignment(db: &Database, event_id: i64) -> rusqlite::Result<AssignmentExplanation> {
+    let event = db
+        .get_event(event_id)?
+        .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+    let engine = RuleEngine::load(db)?;
+
+    let rules: Vec<RuleExplanation> = engine
+        .rules
+        .iter()
+        .map(|rule| {
+            let check = check_rule(rule, &event);
+            RuleExplanation {
+                rule: rule.clone(),
+                matched: check.is_match(),
+                check,
+            }
+        })
+        .collect();
+    let winning_rule_id = rules.iter().find(|r| r.matched).and_then(|r| r.rule.id);
+
+    Ok(AssignmentExplanation {
+        event_id,
+        project_id: event.project_id,
+        assignment_source: event.assignment_source,
+        assignment_rule_id: event.assignment_rule_id,
+        winning_rule_id,
+        rules,
+    })
+}
+
+/// Event start converted from stored UTC seconds to the user's local time zone
+fn local_start(event: &Event) -> Option<chrono::DateTime<Local>> {
+    Local.timestamp_opt(event.start_date, 0).single()
+}
+
+fn local_time_matches(
+    event: &Event,
+    weekdays: &[Weekday],
+    after: Option<NaiveTime>,
+    before: Option<NaiveTime>,
+) -> bool {
+    let Some(start) = local_start(event) else {
+        return false;
+    };
+
+    if !weekdays.is_empty() && !weekdays.contains(&start.weekday()) {
+        return false;
+    }
+
+    let time = start.time();
+    match (after, before) {
+        (Some(after), Some(before)) if after > before => time >= after || time < before,
+        (after, before) => {
+            after.is_none_or(|after| time >= after) && before.is_none_or(|before| time < before)
+        }
+    }
+}
+
+fn calendar_data(event: &Event) -> Option<CalendarEventData> {
+    serde_json::from_str(event.type_specific_data.as_deref()?).ok()
+}
+
+fn browser_data(event: &Event) -> Option<BrowserHistoryEventData> {
+    serde_json::from_str(event.type_specific_data.as_deref()?).ok()
+}
+
+fn git_data(event: &Event) -> Option<GitEventData> {
+    serde_json::from_str(event.type_specific_data.as_deref()?).ok()
+}
+
+/// SQLite `LIKE` semantics: `%` matches any run of characters, `_` matches exactly one,
+/// and comparison is case-insensitive for ASCII only
+pub fn like_matches(pattern: &str, text: &str) -> bool {
+    wildcard_matches(pattern, text, '%', '_', |a, b| a.eq_ignore_ascii_case(&b))
+}
+
+/// Shell-style glob: `*` matches any run of characters, `?` matches exactly one (case-sensitive)
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    wildcard_matches(pattern, text, '*', '?', |a, b| a == b)
+}
+
+fn wildcard_matches(
+    pattern: &str,
+    text: &str,
+    any: char,
+    one: char,
+    eq: impl Fn(char, char) -> bool,
+) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == any {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == one || eq(pattern[p], text[t])) {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last wildcard absorb one more character and retry
+            backtrack = Some((star, matched + 1));
+            p = star + 1;
+            t = matched + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == any)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(rule_type: &str, match_value: &str) -> ProjectRule {
+        ProjectRule {
+            id: Some(1),
+            project_id: 1,
+            rule_type: rule_type.to_string(),
+            match_value: match_value.to_string(),
+            condition: RuleCondition::from_rule(rule_type, match_value).ok(),
+            priority: 0,
+            created_at: 0,
+        }
+    }
+
+    fn ranked(id: i64, priority: i64, rule_type: &str, match_value: &str) -> ProjectRule {
+        ProjectRule {
+            id: Some(id),
+            project_id: id * 10,
+            priority,
+            ..rule(rule_type, match_value)
+        }
+    }
+
+    fn event(event_type: &str, title: &str, type_specific_data: Option<&str>) -> Event {
+        Event {
+            id: None,
+            event_type: event_type.to_string(),
+            title: title.to_string(),
+            start_date: 0,
+            end_date: 0,
+            external_id: None,
+            external_link: None,
+            type_specific_data: type_specific_data.map(|s| s.to_string()),
+            project_id: None,
+            organizer_id: None,
+            repository_path: None,
+            domain: None,
+            series_id: None,
+            assignment_source: Default::default(),
+            assignment_rule_id: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_like_matches() {
+        assert!(like_matches("%standup%", "Daily Standup"));
+        assert!(like_matches(
+            "https://github.com/%",
+            "https://github.com/a/b"
+        ));
+        assert!(like_matches("a_c", "abc"));
+        assert!(like_matches("%a%b%", "xxaxxbxx"));
+        assert!(!like_matches("a_c", "abbc"));
+        assert!(!like_matches("%standup", "standup notes"));
+        assert!(like_matches("%", ""));
+    }
+
+    #[test]
+    fn test_title_pattern_only_matches_calendar_events() {
+        let r = rule("title_pattern", "What is");
+        assert!(rule_matches(&r, &event("calendar", "what is XX", None)));
+        assert!(!rule_matches(&r, &event("git", "What is XX", None)));
+    }
+
+    #[test]
+    fn test_organizer_and_url_rules_read_type_specific_data() {
+        let cal = event(
+            "calendar",
+            "Sync",
+            Some(r#"{"is_all_day":false,"organizer":"Ada Lovelace"}"#),
+        );
+        assert!(rule_matches(&rule("organizer", "Ada Lovelace"), &cal));
+        assert!(!rule_matches(&rule("organizer", "Ada"), &cal));
+
+        let visit = event(
+            "browser_history",
+            "Doc",
+            Some(
+                r#"{"url":"https://docs.google.com/d/1","domain":"docs.google.com","visit_count":1}"#,
+            ),
+        );
+        assert!(rule_matches(
+            &rule("url_pattern", "%docs.google.com/d/%"),
+            &visit
+        ));
+    }
+
+    #[test]
+    fn test_preview_reports_higher_priority_claims() {
+        let engine = RuleEngine {
+            rules: vec![ranked(1, 0, "title_pattern", "standup")],
+        };
+        let events = vec![
+            event("calendar", "Team standup", None),
+            event("calendar", "Standup: what is XX", None),
+            event("calendar", "What is XX", None),
+        ];
+        let candidate = ProjectRule {
+            id: None,
+            ..ranked(0, 1, "title_pattern", "what is")
+        };
+
+        let preview = engine.preview(&candidate, &events);
+        assert_eq!(preview.match_count, 2);
+        assert_eq!(preview.claimed_count, 1);
+        assert_eq!(
+            preview.matches[0].status,
+            PreviewStatus::ClaimedByHigherPriority {
+                rule_id: Some(1),
+                project_id: 10
+            }
+        );
+        assert_eq!(preview.matches[1].status, PreviewStatus::Claimed);
+    }
+
+    #[test]
+    fn test_preview_of_edited_rule_replaces_saved_version() {
+        let engine = RuleEngine {
+            rules: vec![
+                ranked(1, 0, "title_pattern", "standup"),
+                ranked(2, 1, "title_pattern", "planning"),
+            ],
+        };
+        let events = vec![event("calendar", "Standup", None)];
+
+        // Moving rule 1 to a new pattern must not leave its old version claiming events
+        let edited = ranked(1, 0, "title_pattern", "stand");
+        let preview = engine.preview(&edited, &events);
+        assert_eq!(preview.matches[0].status, PreviewStatus::Claimed);
+        assert_eq!(preview.matches[0].current_rule_id, Some(1));
+    }
+
+    #[test]
+    fn test_check_rule_reports_mismatch_reasons() {
+        let standup = rule("title_pattern", "standup");
+        assert_eq!(
+            check_rule(&standup, &event("git", "standup", None)),
+            RuleCheck::WrongEventType {
+                expected: vec!["calendar".to_string()],
+                actual: "git".to_string()
+            }
+        );
+        assert_eq!(
+            check_rule(&standup, &event("calendar", "Planning", None)),
+            RuleCheck::PatternMismatch {
+                field: "title",
+                pattern: "%standup%".to_string(),
+                value: Some("Planning".to_string())
+            }
+        );
+        assert_eq!(
+            check_rule(&rule("organizer", "Ada"), &event("calendar", "Sync", None)),
+            RuleCheck::ValueMismatch {
+                field: "organizer",
+                value: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_compound_regex_condition() {
+        let condition = r#"{"type":"all","conditions":[
+            {"type":"field","field":"title","matcher":{"op":"regex","pattern":"standup|sync","case_insensitive":true}},
+            {"type":"field","field":"organizer","matcher":{"op":"equals","value":"Ada"}},
+            {"type":"not","condition":{"type":"all_day"}}
+        ]}"#;
+        let r = rule("condition", condition);
+        assert!(r.condition.is_some());
+
+        let timed = event(
+            "calendar",
+            "Weekly SYNC",
+            Some(r#"{"is_all_day":false,"organizer":"Ada"}"#),
+        );
+        let all_day = event(
+            "calendar",
+            "Weekly sync",
+            Some(r#"{"is_all_day":true,"organizer":"Ada"}"#),
+        );
+        assert!(rule_matches(&r, &timed));
+        assert!(!rule_matches(&r, &all_day));
+        assert_eq!(
+            check_rule(&r, &all_day),
+            RuleCheck::Not {
+                inner: Box::new(RuleCheck::Matched {
+                    field: "is_all_day",
+                    value: "true".to_string()
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_conditions_are_rejected() {
+        assert!(RuleCondition::from_rule(
+            "condition",
+            r#"{"type":"field","field":"title","matcher":{"op":"regex","pattern":"("}}"#
+        )
+        .is_err());
+        assert!(RuleCondition::from_rule("nonsense", "x").is_err());
+    }
+
+    #[test]
+    fn test_attendee_and_calendar_rules() {
+        let one_on_one = event(
+            "calendar",
+            "Catch up",
+            Some(
+                r#"{"is_all_day":false,"attendees":["Ada","Grace"],"calendar_name":"Work","calendar_account":"ada@example.com"}"#,
+            ),
+        );
+        assert!(rule_matches(&rule("attendee", "Grace"), &one_on_one));
+        assert!(!rule_matches(&rule("attendee", "Linus"), &one_on_one));
+        assert!(rule_matches(&rule("attendee_count", "2"), &one_on_one));
+        assert!(!rule_matches(&rule("attendee_count", "3"), &one_on_one));
+        assert!(rule_matches(&rule("calendar", "Work"), &one_on_one));
+        assert!(!rule_matches(&rule("calendar", "Customers"), &one_on_one));
+        assert!(RuleCondition::from_rule("attendee_count", "many").is_err());
+    }
+
+    #[test]
+    fn test_recurring_series_rule() {
+        let mut standup = event("calendar", "Standup", Some(r#"{"is_all_day":false}"#));
+        let series_rule = rule("recurring_series", "standup-uid");
+        assert!(!rule_matches(&series_rule, &standup));
+
+        standup.series_id = Some("standup-uid".to_string());
+        assert!(rule_matches(&series_rule, &standup));
+        assert!(!rule_matches(
+            &rule("recurring_series", "retro-uid"),
+            &standup
+        ));
+    }
+
+    #[test]
+    fn test_git_branch_ticket_and_activity_rules() {
+        let commit = event(
+            "git",
+            "PROJ-123: fix login redirect",
+            Some(
+                r#"{"repository_id":"r","repository_name":"mono","activity_type":"commit","ref_name":"feature/PROJ-123-login"}"#,
+            ),
+        );
+        assert!(rule_matches(
+            &rule("branch_pattern", "feature/PROJ-*"),
+            &commit
+        ));
+        assert!(!rule_matches(
+            &rule("branch_pattern", "feature/OPS-*"),
+            &commit
+        ));
+        assert!(rule_matches(&rule("ticket_prefix", "proj"), &commit));
+        assert!(!rule_matches(&rule("ticket_prefix", "PRO"), &commit));
+        assert!(rule_matches(&rule("git_activity", "commit"), &commit));
+        assert!(!rule_matches(&rule("git_activity", "checkout"), &commit));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("feature/*", "feature/a_b"));
+        assert!(glob_matches("release-?.x", "release-2.x"));
+        assert!(!glob_matches("Feature/*", "feature/a"));
+    }
+
+    #[test]
+    fn test_local_time_condition() {
+        fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> Event {
+            let mut e = event("git", "commit", None);
+            e.start_date = Local
+                .with_ymd_and_hms(year, month, day, hour, minute, 0)
+                .single()
+                .unwrap()
+                .timestamp();
+            e
+        }
+
+        // 2024-06-01 is a Saturday
+        let weekend = RuleCondition::LocalTime {
+            weekdays: vec![Weekday::Sat, Weekday::Sun],
+            after: None,
+            before: None,
+        };
+        assert!(weekend.matches(&at(2024, 6, 1, 23, 30)));
+        assert!(!weekend.matches(&at(2024, 6, 3, 10, 0)));
+
+        let early: RuleCondition =
+            serde_json::from_str(r#"{"type":"local_time","before":"09:00"}"#).unwrap();
+        assert!(early.matches(&at(2024, 6, 3, 8, 59)));
+        assert!(!early.matches(&at(2024, 6, 3, 9, 0)));
+
+        let overnight: RuleCondition =
+            serde_json::from_str(r#"{"type":"local_time","after":"22:00","before":"06:00"}"#)
+                .unwrap();
+        assert!(overnight.matches(&at(2024, 6, 3, 23, 0)));
+        assert!(overnight.matches(&at(2024, 6, 4, 5, 0)));
+        assert!(!overnight.matches(&at(2024, 6, 4, 12, 0)));
+        assert_eq!(
+            overnight.check(&at(2024, 6, 4, 12, 0)),
+            RuleCheck::ValueMismatch {
+                field: "local_time",
+                value: Some("Tue 12:00".to_string()),
+            }
+        );
+
+        let empty: RuleCondition = serde_json::from_str(r#"{"type":"local_time"}"#).unwrap();
+        assert!(empty.validate().is_err());
+    }
+}
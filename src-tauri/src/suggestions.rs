@@ -0,0 +1,280 @@
+use crate::db::{AssignmentSource, Database, Event, ProjectRule};
+use crate::rules::{field_values, RuleCondition, RuleEngine, RuleField, RulePreview};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// A candidate needs at least this many manually assigned events behind it
+const MIN_SUPPORT: usize = 2;
+const MIN_CONFIDENCE: f64 = 0.6;
+const MIN_TOKEN_LEN: usize = 4;
+
+/// Title words too generic to say anything about a project
+const STOP_WORDS: &[&str] = &[
+    "meeting", "call", "sync", "with", "from", "weekly", "daily", "chat",
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RuleSuggestion {
+    pub rule: ProjectRule, // Unsaved candidate (no id), at the lowest priority
+    pub confidence: f64,
+    pub supporting_event_count: usize, // Manual assignments to the suggested project
+    pub conflicting_event_count: usize, // Manual assignments of the same pattern elsewhere
+    pub preview: RulePreview,          // What saving the rule would claim
+}
+
+/// Mine manual assignments for shared organizers, repositories, domains and title tokens,
+/// and propose rules that would reproduce them. Patterns that already have a rule are skipped.
+pub fn suggest_rules(db: &Database) -> rusqlite::Result<Vec<RuleSuggestion>> {
+    let events = db.get_all_events()?;
+    let existing: BTreeSet<(String, String)> = db
+        .get_project_rules(None)?
+        .into_iter()
+        .map(|rule| (rule.rule_type, rule.match_value))
+        .collect();
+    let engine = RuleEngine::load(db)?;
+    let priority = db.next_rule_priority();
+
+    // Manual assignment counts per project, for each candidate pattern
+    let mut tallies: HashMap<(&'static str, String), HashMap<i64, usize>> = HashMap::new();
+    for event in &events {
+        let Some(project_id) = event.project_id else {
+            continue;
+        };
+        if event.assignment_source != AssignmentSource::Manual {
+            continue;
+        }
+        for key in candidate_keys(event) {
+            *tallies
+                .entry(key)
+                .or_default()
+                .entry(project_id)
+                .or_default() += 1;
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for ((rule_type, match_value), by_project) in tallies {
+        if existing.contains(&(rule_type.to_string(), match_value.clone())) {
+            continue;
+        }
+
+        // Ties go to the lower project id so results are stable
+        let Some((&project_id, &support)) = by_project
+            .iter()
+            .max_by_key(|(&project_id, &count)| (count, std::cmp::Reverse(project_id)))
+        else {
+            continue;
+        };
+        let total: usize = by_project.values().sum();
+        let conflicting = total - support;
+        let confidence = confidence(support, conflicting);
+        if support < MIN_SUPPORT || confidence < MIN_CONFIDENCE {
+            continue;
+        }
+
+        let Ok(condition) = RuleCondition::from_rule(rule_type, &match_value) else {
+            continue;
+        };
+        let rule = ProjectRule {
+            id: None,
+            project_id,
+            rule_type: rule_type.to_string(),
+            match_value,
+            condition: Some(condition),
+            priority,
+            created_at: 0,
+        };
+        let preview = engine.preview(&rule, &events);
+
+        suggestions.push(RuleSuggestion {
+            rule,
+            confidence,
+            supporting_event_count: support,
+            conflicting_event_count: conflicting,
+            preview,
+        });
+    }
+
+    suggestions.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then(b.supporting_event_count.cmp(&a.supporting_event_count))
+            .then(b.preview.claimed_count.cmp(&a.preview.claimed_count))
+            .then_with(|| a.rule.match_value.cmp(&b.rule.match_value))
+    });
+
+    Ok(suggestions)
+}
+
+/// Share of manual assignments that agree, smoothed so a handful of events can't reach 1.0
+fn confidence(support: usize, conflicting: usize) -> f64 {
+    support as f64 / (support + conflicting + 1) as f64
+}
+
+/// Rule patterns an event could be matched by, as (rule_type, match_value)
+fn candidate_keys(event: &Event) -> BTreeSet<(&'static str, String)> {
+    let mut keys = BTreeSet::new();
+
+    match event.event_type.as_str() {
+        "calendar" => {
+            for organizer in field_values(RuleField::Organizer, event) {
+                keys.insert(("organizer", organizer));
+            }
+            for token in title_tokens(&event.title) {
+                keys.insert(("title_pattern", token));
+            }
+        }
+        "git" => {
+            if let Some(path) = &event.repository_path {
+                keys.insert(("repository", path.clone()));
+            }
+        }
+        "browser_history" => {
+            if let Some(domain) = &event.domain {
+                keys.insert(("domain", domain.clone()));
+            }
+        }
+        _ => {}
+    }
+
+    keys
+}
+
+fn title_tokens(title: &str) -> BTreeSet<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .map(|token| token.to_lowercase())
+        .filter(|token| {
+            token.chars().count() >= MIN_TOKEN_LEN
+                && !token.chars().all(|c| c.is_ascii_digit())
+                && !STOP_WORDS.contains(&token.as_str())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::PreviewStatus;
+
+    fn event(event_type: &str, external_id: &str, title: &str) -> Event {
+        Event {
+            id: None,
+            event_type: event_type.to_string(),
+            title: title.to_string(),
+            start_date: 0,
+            end_date: 0,
+            external_id: Some(external_id.to_string()),
+            external_link: None,
+            type_specific_data: None,
+            project_id: None,
+            organizer_id: None,
+            repository_path: None,
+            domain: None,
+            series_id: None,
+            assignment_source: AssignmentSource::Unassigned,
+            assignment_rule_id: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_suggestions_from_manual_assignments() {
+        let db = Database::new(":memory:".into()).unwrap();
+        db.init_schema().unwrap();
+        let apollo = db.create_project("Apollo", None).unwrap();
+        let zeus = db.create_project("Zeus", None).unwrap();
+        db.create_project_rule(zeus, "domain", "zeus.dev").unwrap();
+
+        let mut assigned = Vec::new();
+        for (title, project_id) in [
+            ("Apollo planning", apollo),
+            ("Apollo review", apollo),
+            ("Apollo retro", apollo),
+            // Roadmap is split 2-1, below the confidence threshold
+            ("Roadmap draft", apollo),
+            ("Roadmap final", zeus),
+            ("Roadmap notes", zeus),
+        ] {
+            assigned.push((event("calendar", title, title), project_id));
+        }
+        for id in ["c1", "c2"] {
+            let mut commit = event("git", id, "Commit");
+            commit.repository_path = Some("acme/zeus".to_string());
+            assigned.push((commit, zeus));
+        }
+        // Already covered by the saved domain rule
+        for id in ["v1", "v2", "v3"] {
+            let mut visit = event("browser_history", id, "Docs");
+            visit.domain = Some("zeus.dev".to_string());
+            assigned.push((visit, zeus));
+        }
+        for (event, project_id) in &assigned {
+            let (id, _) = db.upsert_event(event).unwrap();
+            db.assign_event_to_project(id, Some(*project_id)).unwrap();
+        }
+        // Unassigned events only count towards what a rule would claim
+        let (launch, _) = db
+            .upsert_event(&event("calendar", "launch", "Apollo launch"))
+            .unwrap();
+        let mut commit = event("git", "c3", "Commit");
+        commit.repository_path = Some("acme/zeus".to_string());
+        let (unassigned_commit, _) = db.upsert_event(&commit).unwrap();
+
+        let suggestions = suggest_rules(&db).unwrap();
+        let summary: Vec<(&str, &str, i64, usize, usize)> = suggestions
+            .iter()
+            .map(|s| {
+                (
+                    s.rule.rule_type.as_str(),
+                    s.rule.match_value.as_str(),
+                    s.rule.project_id,
+                    s.supporting_event_count,
+                    s.conflicting_event_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("title_pattern", "apollo", apollo, 3, 0),
+                ("repository", "acme/zeus", zeus, 2, 0),
+            ]
+        );
+        assert_eq!(suggestions[0].confidence, 0.75);
+
+        for (suggestion, claimed) in suggestions.iter().zip([launch, unassigned_commit]) {
+            assert_eq!(suggestion.preview.claimed_count, 1);
+            let claimed_ids: Vec<Option<i64>> = suggestion
+                .preview
+                .matches
+                .iter()
+                .filter(|m| m.status == PreviewStatus::Claimed)
+                .map(|m| m.event.id)
+                .collect();
+            assert_eq!(claimed_ids, vec![Some(claimed)]);
+            // The manual assignments behind it stay pinned
+            assert_eq!(
+                suggestion.preview.matches.len(),
+                suggestion.supporting_event_count + 1
+            );
+        }
+    }
+
+    #[test]
+    fn test_title_tokens() {
+        let tokens = title_tokens("Weekly sync: Apollo / 2024 roadmap");
+        assert_eq!(
+            tokens.into_iter().collect::<Vec<_>>(),
+            vec!["apollo".to_string(), "roadmap".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_confidence_prefers_more_evidence() {
+        assert!(confidence(10, 0) > confidence(2, 0));
+        assert!(confidence(2, 0) >= MIN_CONFIDENCE);
+        assert!(confidence(2, 1) < MIN_CONFIDENCE);
+    }
+}
@@ -2,8 +2,11 @@ use chrono::DateTime;
 
 use crate::browser::BrowserVisit;
 use crate::calendar::CalendarEvent;
-use crate::db::{BrowserHistoryEventData, CalendarEventData, Database, Event, GitEventData};
+use crate::db::{
+    AssignmentSource, BrowserHistoryEventData, CalendarEventData, Database, Event, GitEventData,
+};
 use crate::git::GitActivity;
+use crate::rules::RuleEngine;
 
 /// Clean up notes by trimming consecutive blank lines
 fn clean_notes(notes: Option<String>) -> Option<String> {
@@ -41,9 +44,47 @@ fn clean_notes(notes: Option<String>) -> Option<String> {
     })
 }
 
-pub fn sync_single_event(db: &Database, cal_event: &CalendarEvent) -> Result<(bool, i64), String> {
+/// Settings key for what happens to cancelled events and declined invitations
+const CANCELLED_EVENTS_SETTING: &str = "calendar_cancelled_events";
+
+/// What sync does with cancelled events and invitations I declined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CancelledEventHandling {
+    #[default]
+    Skip, // Leave them out, removing copies synced before the change
+    Mark, // Keep them with their status recorded
+}
+
+impl CancelledEventHandling {
+    pub fn load(db: &Database) -> rusqlite::Result<Self> {
+        Ok(match db.get_setting(CANCELLED_EVENTS_SETTING)?.as_deref() {
+            Some("mark") => CancelledEventHandling::Mark,
+            _ => CancelledEventHandling::Skip,
+        })
+    }
+}
+
+/// Returns (was_new, event_id, auto_assigned), or None if the event was skipped
+pub fn sync_single_event(
+    db: &Database,
+    cal_event: &CalendarEvent,
+    rules: &RuleEngine,
+    cancelled: CancelledEventHandling,
+) -> Result<Option<(bool, i64, bool)>, String> {
     let external_id = cal_event.event_id.clone();
 
+    // A manually assigned copy is kept and marked instead, so its project isn't lost
+    if cancelled == CancelledEventHandling::Skip
+        && cal_event.is_cancelled_or_declined()
+        && !db
+            .is_event_pinned("calendar", &external_id)
+            .map_err(|e| format!("Failed to look up cancelled event: {}", e))?
+    {
+        db.delete_event_by_external_id("calendar", &external_id)
+            .map_err(|e| format!("Failed to remove cancelled event: {}", e))?;
+        return Ok(None);
+    }
+
     let organizer_id = if let Some(org_name) = &cal_event.organizer {
         let email = cal_event.organizer_email.as_deref();
         match db.upsert_contact(org_name, email) {
@@ -70,6 +111,10 @@ pub fn sync_single_event(db: &Database, cal_event: &CalendarEvent) -> Result<(bo
         } else {
             Some(cal_event.attendees.clone())
         },
+        calendar_name: cal_event.calendar_name.clone(),
+        calendar_account: cal_event.calendar_account.clone(),
+        status: cal_event.status,
+        participant_status: cal_event.participant_status,
     };
 
     let type_specific_json = serde_json::to_string(&type_specific_data)
@@ -96,6 +141,9 @@ pub fn sync_single_event(db: &Database, cal_event: &CalendarEvent) -> Result<(bo
         organizer_id,
         repository_path: None,
         domain: None,
+        series_id: cal_event.series_id.clone(),
+        assignment_source: AssignmentSource::Unassigned,
+        assignment_rule_id: None,
         created_at: 0,
         updated_at: 0,
     };
@@ -104,14 +152,17 @@ pub fn sync_single_event(db: &Database, cal_event: &CalendarEvent) -> Result<(bo
         .upsert_event(&event)
         .map_err(|e| format!("Failed to insert event: {}", e))?;
 
-    Ok((was_new, event_id))
+    let auto_assigned = assign_by_rules(db, rules, &event, event_id)?;
+
+    Ok(Some((was_new, event_id, auto_assigned)))
 }
 
 pub fn sync_git_activity(
     db: &Database,
     git_activity: &GitActivity,
     repo_info: &crate::git::GitRepository,
-) -> Result<(bool, i64), String> {
+    rules: &RuleEngine,
+) -> Result<(bool, i64, bool), String> {
     let external_id = format!("{}:{}", git_activity.repository_id, git_activity.timestamp);
 
     let type_specific_data = GitEventData {
@@ -146,6 +197,9 @@ pub fn sync_git_activity(
         organizer_id: None,
         repository_path: repo_info.repository_path.clone(),
         domain: None,
+        series_id: None,
+        assignment_source: AssignmentSource::Unassigned,
+        assignment_rule_id: None,
         created_at: 0,
         updated_at: 0,
     };
@@ -154,15 +208,19 @@ pub fn sync_git_activity(
         .upsert_event(&event)
         .map_err(|e| format!("Failed to insert git event: {}", e))?;
 
-    Ok((was_new, event_id))
+    let auto_assigned = assign_by_rules(db, rules, &event, event_id)?;
+
+    Ok((was_new, event_id, auto_assigned))
 }
 
 pub fn sync_browser_visit(
     db: &Database,
     visit: &BrowserVisit,
+    profile_label: Option<&str>,
     discovered_repos: &[String],
     github_orgs: &[String],
-) -> Result<(bool, i64), String> {
+    rules: &RuleEngine,
+) -> Result<(bool, i64, bool), String> {
     let domain = extract_domain(&visit.url);
     let repository_path = extract_repository_path_from_url(&visit.url);
 
@@ -174,7 +232,7 @@ pub fn sync_browser_visit(
     });
 
     if !should_include && repository_path.is_some() {
-        return Ok((false, 0));
+        return Ok((false, 0, false));
     }
 
     use std::collections::hash_map::DefaultHasher;
@@ -190,6 +248,7 @@ pub fn sync_browser_visit(
         page_title: visit.title.clone(),
         visit_count: visit.visit_count,
         repository_path: repository_path.clone(),
+        profile_label: profile_label.map(str::to_string),
     };
 
     let type_specific_json = serde_json::to_string(&type_specific_data)
@@ -215,6 +274,9 @@ pub fn sync_browser_visit(
         organizer_id: None,
         repository_path,
         domain: Some(domain),
+        series_id: None,
+        assignment_source: AssignmentSource::Unassigned,
+        assignment_rule_id: None,
         created_at: 0,
         updated_at: 0,
     };
@@ -223,10 +285,23 @@ pub fn sync_browser_visit(
         .upsert_event(&event)
         .map_err(|e| format!("Failed to insert browser event: {}", e))?;
 
-    Ok((was_new, event_id))
+    let auto_assigned = assign_by_rules(db, rules, &event, event_id)?;
+
+    Ok((was_new, event_id, auto_assigned))
+}
+
+/// Run the rule engine over a freshly upserted event
+fn assign_by_rules(
+    db: &Database,
+    rules: &RuleEngine,
+    event: &Event,
+    event_id: i64,
+) -> Result<bool, String> {
+    db.apply_rule_match(event_id, rules.find_match(event))
+        .map_err(|e| format!("Failed to apply project rules: {}", e))
 }
 
-fn extract_domain(url: &str) -> String {
+pub(crate) fn extract_domain(url: &str) -> String {
     if let Some(start) = url.find("://") {
         let after_protocol = &url[start + 3..];
         if let Some(end) = after_protocol.find('/') {
@@ -299,3 +374,60 @@ fn truncate_url(url: &str) -> String {
         url.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{EventStatus, ParticipantStatus};
+
+    fn meeting(external_id: &str) -> CalendarEvent {
+        CalendarEvent {
+            event_id: external_id.to_string(),
+            title: "Vendor call".to_string(),
+            start_date: "2024-03-04T09:00:00+00:00".to_string(),
+            end_date: "2024-03-04T10:00:00+00:00".to_string(),
+            location: None,
+            notes: None,
+            is_all_day: false,
+            attendees: Vec::new(),
+            organizer: None,
+            organizer_email: None,
+            calendar_name: None,
+            calendar_account: None,
+            status: EventStatus::Confirmed,
+            participant_status: Some(ParticipantStatus::Accepted),
+            series_id: None,
+        }
+    }
+
+    #[test]
+    fn test_declining_keeps_manually_assigned_meetings() {
+        let db = Database::new(":memory:".into()).unwrap();
+        db.init_schema().unwrap();
+        let rules = RuleEngine::load(&db).unwrap();
+        let skip = CancelledEventHandling::Skip;
+        let acme = db.create_project("Acme", None).unwrap();
+        let (_, pinned, _) = sync_single_event(&db, &meeting("pinned"), &rules, skip)
+            .unwrap()
+            .unwrap();
+        db.assign_event_to_project(pinned, Some(acme)).unwrap();
+        let (_, other, _) = sync_single_event(&db, &meeting("other"), &rules, skip)
+            .unwrap()
+            .unwrap();
+
+        for external_id in ["pinned", "other"] {
+            let declined = CalendarEvent {
+                participant_status: Some(ParticipantStatus::Declined),
+                ..meeting(external_id)
+            };
+            sync_single_event(&db, &declined, &rules, skip).unwrap();
+        }
+
+        assert!(db.get_event(other).unwrap().is_none());
+        let pinned = db.get_event(pinned).unwrap().unwrap();
+        assert_eq!(pinned.project_id, Some(acme));
+        assert_eq!(pinned.assignment_source, AssignmentSource::Manual);
+        // Recorded as declined, so totals and estimates leave it out
+        assert!(crate::rules::is_cancelled_or_declined(&pinned));
+    }
+}
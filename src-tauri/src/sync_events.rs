@@ -16,6 +16,8 @@ pub enum SyncEvent {
         source: SyncSource,
         new_events: usize,
         updated_events: usize,
+        auto_assigned_events: usize,
+        removed_events: usize, // Stored events no longer at the source
     },
     Completed {
         total_new: usize,
@@ -82,6 +84,8 @@ pub fn emit_source_completed(
     source: SyncSource,
     new_events: usize,
     updated_events: usize,
+    auto_assigned_events: usize,
+    removed_events: usize,
 ) {
     emit_sync_event(
         app,
@@ -89,6 +93,8 @@ pub fn emit_source_completed(
             source,
             new_events,
             updated_events,
+            auto_assigned_events,
+            removed_events,
         },
     );
 }
@@ -0,0 +1,528 @@
+use crate::db::Database;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Bumped whenever the document layout changes incompatibly
+pub const TAXONOMY_VERSION: u32 = 1;
+
+/// Portable snapshot of projects and their rules, for sharing between installs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaxonomyDocument {
+    pub version: u32,
+    pub projects: Vec<TaxonomyProject>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaxonomyProject {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<TaxonomyRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaxonomyRule {
+    pub rule_type: String,
+    pub match_value: String,
+    pub priority: i64, // Relative order across the whole document; lower wins
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaxonomyFormat {
+    Json,
+    Toml,
+}
+
+impl TaxonomyFormat {
+    /// Pick the format from a file extension, defaulting to JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => TaxonomyFormat::Toml,
+            _ => TaxonomyFormat::Json,
+        }
+    }
+}
+
+impl TaxonomyDocument {
+    pub fn to_string(&self, format: TaxonomyFormat) -> rusqlite::Result<String> {
+        match format {
+            TaxonomyFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+            TaxonomyFormat::Toml => toml::to_string_pretty(self)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+        }
+    }
+
+    pub fn parse(content: &str, format: TaxonomyFormat) -> rusqlite::Result<Self> {
+        let document: TaxonomyDocument = match format {
+            TaxonomyFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            TaxonomyFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| {
+            rusqlite::Error::InvalidParameterName(format!("Invalid taxonomy file: {}", e))
+        })?;
+
+        if document.version > TAXONOMY_VERSION {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Taxonomy file version {} is newer than supported version {}",
+                document.version, TAXONOMY_VERSION
+            )));
+        }
+
+        Ok(document)
+    }
+}
+
+/// What to do when an imported project name already exists
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectConflict {
+    #[default]
+    Merge, // Add the imported rules to the existing project
+    Skip,   // Leave the existing project alone and drop its imported rules
+    Rename, // Create a new project with a numbered name
+}
+
+/// What to do when an imported rule's (rule_type, match_value) belongs to another project
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleConflict {
+    #[default]
+    Keep, // Existing rule wins
+    Replace, // Move the existing rule to the imported project (it keeps its priority)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub on_project_conflict: ProjectConflict,
+    #[serde(default)]
+    pub on_rule_conflict: RuleConflict,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ProjectChange {
+    Create { name: String },
+    Merge { name: String, project_id: i64 },
+    Skip { name: String, project_id: i64 },
+    Rename { name: String, new_name: String },
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RuleChange {
+    Create,
+    Unchanged, // Already present on the same project
+    Keep {
+        existing_project_id: i64,
+    },
+    Replace {
+        rule_id: i64,
+        previous_project_id: i64,
+    },
+    SkippedProject, // The project was skipped, so its rules are too
+    Invalid {
+        error: String,
+    },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RuleImport {
+    pub project_name: String,
+    pub rule_type: String,
+    pub match_value: String,
+    pub change: RuleChange,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub projects: Vec<ProjectChange>,
+    pub rules: Vec<RuleImport>,
+}
+
+/// Snapshot every project with its rules. Priorities are exported as-is.
+pub fn export_taxonomy(db: &Database) -> rusqlite::Result<TaxonomyDocument> {
+    let rules = db.get_project_rules(None)?;
+    let projects = db
+        .get_all_projects()?
+        .into_iter()
+        .map(|project| TaxonomyProject {
+            rules: rules
+                .iter()
+                .filter(|rule| Some(rule.project_id) == project.id)
+                .map(|rule| TaxonomyRule {
+                    rule_type: rule.rule_type.clone(),
+                    match_value: rule.match_value.clone(),
+                    priority: rule.priority,
+                })
+                .collect(),
+            name: project.name,
+            color: project.color,
+        })
+        .collect();
+
+    Ok(TaxonomyDocument {
+        version: TAXONOMY_VERSION,
+        projects,
+    })
+}
+
+/// Merge a document into the database. New rules are appended after the existing ones,
+/// keeping the document's relative order. A dry run reports the same changes and rolls back.
+pub fn import_taxonomy(
+    db: &Database,
+    document: &TaxonomyDocument,
+    options: &ImportOptions,
+) -> rusqlite::Result<ImportReport> {
+    let tx = db.transaction()?;
+
+    let mut projects = Vec::new();
+    let mut project_ids = Vec::new(); // Target project per document project (None if skipped)
+
+    for project in &document.projects {
+        let existing = db.get_project_by_name(&project.name)?;
+        let (change, project_id) = match (existing, options.on_project_conflict) {
+            (None, _) => {
+                let id = db.create_project(&project.name, project.color.as_deref())?;
+                let change = ProjectChange::Create {
+                    name: project.name.clone(),
+                };
+                (change, Some(id))
+            }
+            (Some(existing), ProjectConflict::Merge) => {
+                let id = existing.id.unwrap_or_default();
+                let change = ProjectChange::Merge {
+                    name: project.name.clone(),
+                    project_id: id,
+                };
+                (change, Some(id))
+            }
+            (Some(existing), ProjectConflict::Skip) => {
+                let change = ProjectChange::Skip {
+                    name: project.name.clone(),
+                    project_id: existing.id.unwrap_or_default(),
+                };
+                (change, None)
+            }
+            (Some(_), ProjectConflict::Rename) => {
+                let new_name = unused_project_name(db, &project.name)?;
+                let id = db.create_project(&new_name, project.color.as_deref())?;
+                let change = ProjectChange::Rename {
+                    name: project.name.clone(),
+                    new_name,
+                };
+                (change, Some(id))
+            }
+        };
+        projects.push(change);
+        project_ids.push(project_id);
+    }
+
+    let mut ordered: Vec<(&TaxonomyProject, Option<i64>, &TaxonomyRule)> = document
+        .projects
+        .iter()
+        .zip(&project_ids)
+        .flat_map(|(project, &id)| project.rules.iter().map(move |rule| (project, id, rule)))
+        .collect();
+    ordered.sort_by_key(|(_, _, rule)| rule.priority);
+
+    let mut rules = Vec::new();
+    for (project, project_id, rule) in ordered {
+        let change = match project_id {
+            None => RuleChange::SkippedProject,
+            Some(project_id) => import_rule(db, project_id, rule, options.on_rule_conflict)?,
+        };
+        rules.push(RuleImport {
+            project_name: project.name.clone(),
+            rule_type: rule.rule_type.clone(),
+            match_value: rule.match_value.clone(),
+            change,
+        });
+    }
+
+    if options.dry_run {
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+    }
+
+    Ok(ImportReport {
+        dry_run: options.dry_run,
+        projects,
+        rules,
+    })
+}
+
+fn import_rule(
+    db: &Database,
+    project_id: i64,
+    rule: &TaxonomyRule,
+    on_conflict: RuleConflict,
+) -> rusqlite::Result<RuleChange> {
+    let existing = match db.find_project_rule(&rule.rule_type, &rule.match_value) {
+        Ok(existing) => existing,
+        Err(rusqlite::Error::InvalidParameterName(error)) => {
+            return Ok(RuleChange::Invalid { error })
+        }
+        Err(e) => return Err(e),
+    };
+
+    Ok(match existing {
+        None => {
+            db.create_project_rule(project_id, &rule.rule_type, &rule.match_value)?;
+            RuleChange::Create
+        }
+        Some(existing) if existing.project_id == project_id => RuleChange::Unchanged,
+        Some(existing) => match on_conflict {
+            RuleConflict::Keep => RuleChange::Keep {
+                existing_project_id: existing.project_id,
+            },
+            RuleConflict::Replace => {
+                let rule_id = existing.id.unwrap_or_default();
+                db.update_project_rule(
+                    rule_id,
+                    project_id,
+                    &existing.rule_type,
+                    &existing.match_value,
+                )?;
+                RuleChange::Replace {
+                    rule_id,
+                    previous_project_id: existing.project_id,
+                }
+            }
+        },
+    })
+}
+
+/// "Name (2)", "Name (3)", ... whichever is free first
+fn unused_project_name(db: &Database, name: &str) -> rusqlite::Result<String> {
+    let mut n = 2;
+    loop {
+        let candidate = format!("{} ({})", name, n);
+        if db.get_project_by_name(&candidate)?.is_none() {
+            return Ok(candidate);
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> TaxonomyDocument {
+        TaxonomyDocument {
+            version: TAXONOMY_VERSION,
+            projects: vec![TaxonomyProject {
+                name: "Apollo".to_string(),
+                color: Some("#3B82F6".to_string()),
+                rules: vec![TaxonomyRule {
+                    rule_type: "title_pattern".to_string(),
+                    match_value: "apollo".to_string(),
+                    priority: 0,
+                }],
+            }],
+        }
+    }
+
+    fn rule(rule_type: &str, match_value: &str, priority: i64) -> TaxonomyRule {
+        TaxonomyRule {
+            rule_type: rule_type.to_string(),
+            match_value: match_value.to_string(),
+            priority,
+        }
+    }
+
+    /// Apollo owns "apollo" titles and Other owns x.com
+    fn existing_db() -> (Database, i64, i64) {
+        let db = Database::new(":memory:".into()).unwrap();
+        db.init_schema().unwrap();
+        let apollo = db.create_project("Apollo", None).unwrap();
+        db.create_project_rule(apollo, "title_pattern", "apollo")
+            .unwrap();
+        let other = db.create_project("Other", None).unwrap();
+        db.create_project_rule(other, "domain", "x.com").unwrap();
+        (db, apollo, other)
+    }
+
+    /// Apollo again with one rule of each kind of outcome, plus a new project
+    fn incoming() -> TaxonomyDocument {
+        TaxonomyDocument {
+            version: TAXONOMY_VERSION,
+            projects: vec![
+                TaxonomyProject {
+                    name: "Apollo".to_string(),
+                    color: None,
+                    rules: vec![
+                        rule("domain", "x.com", 1),
+                        rule("title_pattern", "apollo", 0),
+                        rule("repository", "acme/apollo", 2),
+                        rule("bogus", "z", 3),
+                    ],
+                },
+                TaxonomyProject {
+                    name: "Zeus".to_string(),
+                    color: Some("#10B981".to_string()),
+                    rules: vec![rule("repository", "acme/zeus", 4)],
+                },
+            ],
+        }
+    }
+
+    fn changes(report: &ImportReport) -> Vec<&RuleChange> {
+        report.rules.iter().map(|rule| &rule.change).collect()
+    }
+
+    #[test]
+    fn test_import_merges_projects_and_keeps_existing_rules() {
+        let (db, apollo, other) = existing_db();
+        let report = import_taxonomy(&db, &incoming(), &ImportOptions::default()).unwrap();
+
+        let zeus = db.get_project_by_name("Zeus").unwrap().unwrap();
+        assert_eq!(
+            report.projects,
+            vec![
+                ProjectChange::Merge {
+                    name: "Apollo".to_string(),
+                    project_id: apollo,
+                },
+                ProjectChange::Create {
+                    name: "Zeus".to_string(),
+                },
+            ]
+        );
+        // Reported in priority order
+        assert_eq!(report.rules[0].match_value, "apollo");
+        assert!(matches!(
+            changes(&report)[..],
+            [
+                RuleChange::Unchanged,
+                RuleChange::Keep { existing_project_id },
+                RuleChange::Create,
+                RuleChange::Invalid { .. },
+                RuleChange::Create,
+            ] if *existing_project_id == other
+        ));
+        let owner = |match_value: &str| {
+            db.get_project_rules(None)
+                .unwrap()
+                .into_iter()
+                .find(|rule| rule.match_value == match_value)
+                .map(|rule| rule.project_id)
+        };
+        assert_eq!(owner("x.com"), Some(other));
+        assert_eq!(owner("acme/apollo"), Some(apollo));
+        assert_eq!(owner("acme/zeus"), zeus.id);
+    }
+
+    #[test]
+    fn test_import_skips_or_renames_conflicting_projects() {
+        let (db, apollo, other) = existing_db();
+        let skip = ImportOptions {
+            on_project_conflict: ProjectConflict::Skip,
+            ..ImportOptions::default()
+        };
+        let report = import_taxonomy(&db, &incoming(), &skip).unwrap();
+        assert_eq!(
+            report.projects[0],
+            ProjectChange::Skip {
+                name: "Apollo".to_string(),
+                project_id: apollo,
+            }
+        );
+        assert!(changes(&report)[..4]
+            .iter()
+            .all(|change| **change == RuleChange::SkippedProject));
+        assert_eq!(db.get_project_rules(Some(apollo)).unwrap().len(), 1);
+
+        let rename = ImportOptions {
+            on_project_conflict: ProjectConflict::Rename,
+            on_rule_conflict: RuleConflict::Replace,
+            dry_run: false,
+        };
+        let report = import_taxonomy(&db, &incoming(), &rename).unwrap();
+        assert_eq!(
+            report.projects[0],
+            ProjectChange::Rename {
+                name: "Apollo".to_string(),
+                new_name: "Apollo (2)".to_string(),
+            }
+        );
+        let renamed = db.get_project_by_name("Apollo (2)").unwrap().unwrap().id;
+        // Both existing rules move over to the renamed project
+        assert!(matches!(
+            changes(&report)[..2],
+            [
+                RuleChange::Replace { previous_project_id: first, .. },
+                RuleChange::Replace { previous_project_id: second, .. },
+            ] if *first == apollo && *second == other
+        ));
+        assert!(db.get_project_rules(Some(apollo)).unwrap().is_empty());
+        assert_eq!(
+            db.get_project_rules(renamed).unwrap().len(),
+            3 // apollo, x.com and acme/apollo
+        );
+    }
+
+    #[test]
+    fn test_dry_run_reports_changes_without_writing() {
+        let (db, _, _) = existing_db();
+        let before = export_taxonomy(&db).unwrap();
+        let options = ImportOptions {
+            on_project_conflict: ProjectConflict::Rename,
+            on_rule_conflict: RuleConflict::Replace,
+            dry_run: true,
+        };
+
+        let report = import_taxonomy(&db, &incoming(), &options).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.projects.len(), 2);
+        assert!(changes(&report).contains(&&RuleChange::Create));
+        assert_eq!(export_taxonomy(&db).unwrap(), before);
+
+        // The same import for real matches the dry run's report
+        let applied = import_taxonomy(
+            &db,
+            &incoming(),
+            &ImportOptions {
+                dry_run: false,
+                ..options
+            },
+        )
+        .unwrap();
+        assert_eq!(applied.projects, report.projects);
+        assert_eq!(changes(&applied), changes(&report));
+        assert_ne!(export_taxonomy(&db).unwrap(), before);
+    }
+
+    #[test]
+    fn test_document_round_trips_through_json_and_toml() {
+        for format in [TaxonomyFormat::Json, TaxonomyFormat::Toml] {
+            let text = document().to_string(format).unwrap();
+            assert_eq!(TaxonomyDocument::parse(&text, format).unwrap(), document());
+        }
+    }
+
+    #[test]
+    fn test_newer_versions_are_rejected() {
+        let text = r#"{"version": 99, "projects": []}"#;
+        assert!(TaxonomyDocument::parse(text, TaxonomyFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            TaxonomyFormat::from_path(Path::new("rules.TOML")),
+            TaxonomyFormat::Toml
+        );
+        assert_eq!(
+            TaxonomyFormat::from_path(Path::new("rules.json")),
+            TaxonomyFormat::Json
+        );
+    }
+}
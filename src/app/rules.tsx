@@ -18,7 +18,11 @@ import {
 import { useEffect, useState, useRef } from "react";
 import { usePersistedState } from "@/hooks/use-persisted-state";
 import { useProjects } from "@/contexts/projects-context";
-import type { Project, ProjectRule } from "../types/event";
+import type {
+  Project,
+  ProjectRule,
+  RuleApplicationResult,
+} from "../types/event";
 import {
   DndContext,
   closestCenter,
@@ -301,6 +305,12 @@ export function Rules() {
         return `All events containing: "${rule.match_value}"`;
       case "repository":
         return `All activity in: ${rule.match_value}`;
+      case "branch_pattern":
+        return `All activity on branches: ${rule.match_value}`;
+      case "ticket_prefix":
+        return `All commits referencing: ${rule.match_value}-*`;
+      case "recurring_series":
+        return `All occurrences of recurring series: ${rule.match_value}`;
       default:
         return rule.match_value;
     }
@@ -317,9 +327,16 @@ export function Rules() {
     setError(null);
 
     try {
-      const count = await invoke<number>("apply_rules_to_events");
+      const result = await invoke<RuleApplicationResult>(
+        "apply_rules_to_events"
+      );
+      const count = result.assigned_count;
+      const pinned = result.pinned_event_ids.length;
       setApplyRulesResult(
-        `Applied rules to ${count} event${count !== 1 ? "s" : ""}`
+        `Rules newly assigned ${count} event${count !== 1 ? "s" : ""}` +
+          (pinned > 0
+            ? ` (${pinned} manually assigned event${pinned !== 1 ? "s" : ""} kept)`
+            : "")
       );
       setTimeout(() => setApplyRulesResult(null), 3000);
     } catch (err) {
@@ -12,8 +12,51 @@ import {
   DialogHeader,
   DialogTitle,
 } from "@/components/ui/dialog";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
 import { Trash2, Plus, X } from "lucide-react";
 import { useSyncManager } from "@/hooks/sync-hooks";
+import type {
+  BrowserFilterRule,
+  BrowserProfile,
+  FilterDecision,
+  FilterPreview,
+} from "@/types/event";
+
+const BROWSER_NAMES: Record<BrowserProfile["browser"], string> = {
+  zen: "Zen",
+  chrome: "Chrome",
+  arc: "Arc",
+  brave: "Brave",
+  edge: "Edge",
+  safari: "Safari",
+};
+
+const EMPTY_FILTER_RULE: BrowserFilterRule = {
+  id: null,
+  action: "deny",
+  field: "url",
+  kind: "glob",
+  pattern: "",
+};
+
+function describeDecision(decision: FilterDecision) {
+  switch (decision.type) {
+    case "included":
+      return "included";
+    case "allowed":
+      return "allowed by a rule";
+    case "denied":
+      return "denied by a rule";
+    case "default_excluded":
+      return `excluded by default (${decision.pattern})`;
+  }
+}
 
 export function Settings() {
   const { syncState } = useSyncManager();
@@ -21,14 +64,35 @@ export function Settings() {
   const [error, setError] = useState<string | null>(null);
   const [success, setSuccess] = useState<string | null>(null);
   const [gitFolder, setGitFolder] = useState<string>("");
-  const [zenProfilePath, setZenProfilePath] = useState<string>("");
-  const [isDetecting, setIsDetecting] = useState(false);
+  const [icsPath, setIcsPath] = useState<string>("");
+  const [myEmails, setMyEmails] = useState<string>("");
+  const [cancelledEvents, setCancelledEvents] = useState<string>("skip");
+  const [caldavUrl, setCaldavUrl] = useState<string>("");
+  const [caldavUsername, setCaldavUsername] = useState<string>("");
+  const [caldavPassword, setCaldavPassword] = useState<string>("");
+  const [isTestingCaldav, setIsTestingCaldav] = useState(false);
+  const [browserProfiles, setBrowserProfiles] = useState<BrowserProfile[]>([]);
+  const [discoveredProfiles, setDiscoveredProfiles] = useState<
+    BrowserProfile[]
+  >([]);
+  const [filterRules, setFilterRules] = useState<BrowserFilterRule[]>([]);
+  const [defaultFilters, setDefaultFilters] = useState<string[]>([]);
+  const [newFilterRule, setNewFilterRule] =
+    useState<BrowserFilterRule>(EMPTY_FILTER_RULE);
+  const [filterPreview, setFilterPreview] = useState<FilterPreview | null>(
+    null
+  );
+  const [isPreviewingFilter, setIsPreviewingFilter] = useState(false);
   const [githubOrgs, setGithubOrgs] = useState<string[]>([]);
   const [newOrgName, setNewOrgName] = useState<string>("");
 
   useEffect(() => {
     loadGitFolder();
-    loadZenProfilePath();
+    loadIcsPath();
+    loadCalendarOptions();
+    loadCaldavSettings();
+    loadBrowserProfiles();
+    loadFilterRules();
     loadGitHubOrgs();
   }, []);
 
@@ -45,89 +109,271 @@ export function Settings() {
     }
   }
 
-  async function loadZenProfilePath() {
+  async function loadIcsPath() {
     try {
-      const path = await invoke<string | null>("get_zen_profile_path");
+      const path = await invoke<string | null>("get_setting", {
+        key: "ics_calendar_path",
+      });
       if (path) {
-        setZenProfilePath(path);
+        setIcsPath(path);
       }
     } catch (err) {
-      console.error("Error loading zen profile path:", err);
+      console.error("Error loading calendar file path:", err);
     }
   }
 
-  async function browseFolder() {
+  async function browseIcsPath(directory: boolean) {
     try {
       const selected = await open({
-        directory: true,
+        directory,
         multiple: false,
-        defaultPath: gitFolder || undefined,
+        defaultPath: icsPath || undefined,
+        filters: directory
+          ? undefined
+          : [{ name: "iCalendar", extensions: ["ics"] }],
       });
 
       if (selected && typeof selected === "string") {
-        setGitFolder(selected);
+        setIcsPath(selected);
 
-        // Auto-save the selected folder
         try {
           await invoke("set_setting", {
-            key: "git_dev_folder",
+            key: "ics_calendar_path",
             value: selected,
           });
         } catch (err) {
           setError(err as string);
-          console.error("Error saving git folder:", err);
+          console.error("Error saving calendar file path:", err);
         }
       }
     } catch (err) {
-      console.error("Error browsing folder:", err);
+      console.error("Error browsing calendar files:", err);
+    }
+  }
+
+  async function clearIcsPath() {
+    try {
+      await invoke("set_setting", { key: "ics_calendar_path", value: "" });
+      setIcsPath("");
+    } catch (err) {
+      setError(err as string);
+      console.error("Error clearing calendar file path:", err);
+    }
+  }
+
+  async function loadCalendarOptions() {
+    try {
+      const [emails, cancelled] = await Promise.all(
+        ["calendar_my_emails", "calendar_cancelled_events"].map((key) =>
+          invoke<string | null>("get_setting", { key }),
+        ),
+      );
+      setMyEmails(emails ?? "");
+      setCancelledEvents(cancelled ?? "skip");
+    } catch (err) {
+      console.error("Error loading calendar options:", err);
+    }
+  }
+
+  async function saveMyEmails() {
+    try {
+      await invoke("set_setting", {
+        key: "calendar_my_emails",
+        value: myEmails,
+      });
+    } catch (err) {
+      setError(err as string);
+      console.error("Error saving email addresses:", err);
     }
   }
 
-  async function browseZenProfile() {
+  async function saveCancelledEvents(value: string) {
+    setCancelledEvents(value);
+    try {
+      await invoke("set_setting", {
+        key: "calendar_cancelled_events",
+        value,
+      });
+    } catch (err) {
+      setError(err as string);
+      console.error("Error saving cancelled event handling:", err);
+    }
+  }
+
+  async function loadCaldavSettings() {
+    try {
+      // The password is write-only, so it never comes back to the form
+      const [url, username] = await Promise.all(
+        ["caldav_url", "caldav_username"].map((key) =>
+          invoke<string | null>("get_setting", { key }),
+        ),
+      );
+      setCaldavUrl(url ?? "");
+      setCaldavUsername(username ?? "");
+    } catch (err) {
+      console.error("Error loading CalDAV settings:", err);
+    }
+  }
+
+  async function saveCaldavSettings() {
+    try {
+      await invoke("set_setting", { key: "caldav_url", value: caldavUrl });
+      await invoke("set_setting", {
+        key: "caldav_username",
+        value: caldavUsername,
+      });
+      if (caldavPassword) {
+        await invoke("set_setting", {
+          key: "caldav_password",
+          value: caldavPassword,
+        });
+        setCaldavPassword("");
+      }
+      setSuccess("CalDAV settings saved");
+    } catch (err) {
+      setError(err as string);
+      console.error("Error saving CalDAV settings:", err);
+    }
+  }
+
+  async function testCaldavConnection() {
+    setIsTestingCaldav(true);
+    setError(null);
+    setSuccess(null);
+    try {
+      await saveCaldavSettings();
+      const calendars = await invoke<string[]>("test_caldav_connection");
+      setSuccess(
+        calendars.length > 0
+          ? `Connected. Calendars found: ${calendars.join(", ")}`
+          : "Connected, but no calendars were found at this URL",
+      );
+    } catch (err) {
+      setError(err as string);
+    } finally {
+      setIsTestingCaldav(false);
+    }
+  }
+
+  async function browseFolder() {
     try {
       const selected = await open({
         directory: true,
         multiple: false,
-        defaultPath: zenProfilePath || undefined,
+        defaultPath: gitFolder || undefined,
       });
 
       if (selected && typeof selected === "string") {
-        setZenProfilePath(selected);
+        setGitFolder(selected);
 
-        // Auto-save the selected profile path
+        // Auto-save the selected folder
         try {
-          await invoke("set_zen_profile_path", { path: selected });
-          setSuccess("Zen profile path saved successfully");
+          await invoke("set_setting", {
+            key: "git_dev_folder",
+            value: selected,
+          });
         } catch (err) {
           setError(err as string);
-          console.error("Error saving zen profile path:", err);
+          console.error("Error saving git folder:", err);
         }
       }
     } catch (err) {
-      console.error("Error browsing zen profile:", err);
+      console.error("Error browsing folder:", err);
     }
   }
 
-  async function autoDetectZenProfile() {
-    setIsDetecting(true);
+  async function loadBrowserProfiles() {
     try {
-      const detected = await invoke<string | null>(
-        "auto_detect_zen_profile_path"
-      );
-      if (detected) {
-        setZenProfilePath(detected);
-        await invoke("set_zen_profile_path", { path: detected });
-        setSuccess("Zen profile auto-detected successfully");
-      } else {
-        setError(
-          "Could not auto-detect Zen profile. Please select it manually."
+      const [selected, discovered] = await Promise.all([
+        invoke<BrowserProfile[]>("get_browser_profiles"),
+        invoke<BrowserProfile[]>("discover_browser_profiles"),
+      ]);
+      setBrowserProfiles(selected);
+      setDiscoveredProfiles(discovered);
+    } catch (err) {
+      console.error("Error loading browser profiles:", err);
+    }
+  }
+
+  async function saveBrowserProfiles(profiles: BrowserProfile[]) {
+    try {
+      await invoke("set_browser_profiles", { profiles });
+      setBrowserProfiles(profiles);
+    } catch (err) {
+      setError(err as string);
+      console.error("Error saving browser profiles:", err);
+    }
+  }
+
+  function updateBrowserProfile(path: string, changes: Partial<BrowserProfile>) {
+    return saveBrowserProfiles(
+      browserProfiles.map((p) => (p.path === path ? { ...p, ...changes } : p))
+    );
+  }
+
+  async function browseBrowserProfile() {
+    try {
+      const selected = await open({ directory: true, multiple: false });
+      if (selected && typeof selected === "string") {
+        const profile = await invoke<BrowserProfile>(
+          "inspect_browser_profile",
+          { path: selected }
         );
+        await saveBrowserProfiles([...browserProfiles, profile]);
       }
     } catch (err) {
       setError(err as string);
-      console.error("Error auto-detecting zen profile:", err);
+      console.error("Error adding browser profile:", err);
+    }
+  }
+
+  async function loadFilterRules() {
+    try {
+      const [rules, defaults] = await Promise.all([
+        invoke<BrowserFilterRule[]>("get_browser_filter_rules"),
+        invoke<string[]>("get_default_browser_filters"),
+      ]);
+      setFilterRules(rules);
+      setDefaultFilters(defaults);
+    } catch (err) {
+      console.error("Error loading browser filter rules:", err);
+    }
+  }
+
+  async function previewFilterRules(rules: BrowserFilterRule[]) {
+    try {
+      setIsPreviewingFilter(true);
+      setFilterPreview(
+        await invoke<FilterPreview>("preview_browser_filter", { rules })
+      );
+    } catch (err) {
+      setError(err as string);
+      console.error("Error previewing browser filter:", err);
     } finally {
-      setIsDetecting(false);
+      setIsPreviewingFilter(false);
+    }
+  }
+
+  async function addFilterRule() {
+    try {
+      await invoke("create_browser_filter_rule", { rule: newFilterRule });
+      setNewFilterRule(EMPTY_FILTER_RULE);
+      setFilterPreview(null);
+      await loadFilterRules();
+    } catch (err) {
+      setError(err as string);
+      console.error("Error adding browser filter rule:", err);
+    }
+  }
+
+  async function deleteFilterRule(id: number) {
+    try {
+      await invoke("delete_browser_filter_rule", { id });
+      setFilterPreview(null);
+      await loadFilterRules();
+    } catch (err) {
+      setError(err as string);
+      console.error("Error deleting browser filter rule:", err);
     }
   }
 
@@ -248,43 +494,380 @@ export function Settings() {
           </div>
         </div>
 
-        {/* Zen Browser Profile Section */}
+        {/* Calendar Options Section */}
+        <div className="space-y-4 pt-4">
+          <div>
+            <h2 className="text-lg font-semibold">Cancelled Meetings</h2>
+            <p className="text-sm text-muted-foreground mt-1">
+              What to do with cancelled meetings and invitations you declined.
+              Your email addresses are used to find your response in calendar
+              files and CalDAV events.
+            </p>
+          </div>
+          <div className="space-y-2">
+            <Label htmlFor="cancelled-events">Cancelled and Declined</Label>
+            <Select value={cancelledEvents} onValueChange={saveCancelledEvents}>
+              <SelectTrigger id="cancelled-events" className="w-64">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="skip">Leave out of the log</SelectItem>
+                <SelectItem value="mark">Keep and mark them</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="space-y-2">
+            <Label htmlFor="my-emails">Your Email Addresses</Label>
+            <Input
+              id="my-emails"
+              value={myEmails}
+              onChange={(e) => setMyEmails(e.target.value)}
+              onBlur={saveMyEmails}
+              placeholder="me@work.com, me@personal.com"
+            />
+          </div>
+        </div>
+
+        {/* iCalendar Files Section */}
         <div className="space-y-4 pt-4">
           <div>
-            <h2 className="text-lg font-semibold">Zen Browser Profile</h2>
+            <h2 className="text-lg font-semibold">Calendar Files</h2>
             <p className="text-sm text-muted-foreground mt-1">
-              Path to your Zen browser profile folder. Browser history from
-              work-related domains will be automatically synced.
+              An .ics file, or a folder of them, to sync calendar events from.
+              Use this for exported calendars or when the system calendar is
+              not available.
             </p>
           </div>
           <div className="space-y-2">
-            <Label htmlFor="zen-profile">Profile Path</Label>
+            <Label htmlFor="ics-path">File or Folder Path</Label>
             <div className="flex gap-2">
               <Input
-                id="zen-profile"
-                value={zenProfilePath}
-                placeholder="/Users/username/Library/Application Support/zen/Profiles/..."
+                id="ics-path"
+                value={icsPath}
+                placeholder="/Users/username/Calendars/work.ics"
                 className="flex-1"
                 readOnly
               />
-              <Button
-                variant="outline"
-                onClick={autoDetectZenProfile}
-                disabled={isDetecting}
-              >
-                {isDetecting ? "Detecting..." : "Auto-detect"}
+              <Button variant="outline" onClick={() => browseIcsPath(false)}>
+                Choose File...
               </Button>
-              <Button variant="outline" onClick={browseZenProfile}>
-                Browse...
+              <Button variant="outline" onClick={() => browseIcsPath(true)}>
+                Choose Folder...
               </Button>
+              {icsPath && (
+                <Button variant="ghost" size="icon" onClick={clearIcsPath}>
+                  <X className="h-4 w-4" />
+                </Button>
+              )}
             </div>
-            {!zenProfilePath && (
+          </div>
+        </div>
+
+        {/* CalDAV Section */}
+        <div className="space-y-4 pt-4">
+          <div>
+            <h2 className="text-lg font-semibold">CalDAV Server</h2>
+            <p className="text-sm text-muted-foreground mt-1">
+              Sync calendars from a CalDAV server. Use the URL of a calendar or
+              of your calendar home to sync every calendar in it. Only changed
+              events are downloaded on each sync.
+            </p>
+          </div>
+          <div className="space-y-2">
+            <Label htmlFor="caldav-url">Server URL</Label>
+            <Input
+              id="caldav-url"
+              value={caldavUrl}
+              onChange={(e) => setCaldavUrl(e.target.value)}
+              placeholder="https://caldav.example.com/username/"
+            />
+          </div>
+          <div className="flex gap-2">
+            <div className="space-y-2 flex-1">
+              <Label htmlFor="caldav-username">Username</Label>
+              <Input
+                id="caldav-username"
+                value={caldavUsername}
+                onChange={(e) => setCaldavUsername(e.target.value)}
+                autoComplete="off"
+              />
+            </div>
+            <div className="space-y-2 flex-1">
+              <Label htmlFor="caldav-password">Password</Label>
+              <Input
+                id="caldav-password"
+                type="password"
+                value={caldavPassword}
+                onChange={(e) => setCaldavPassword(e.target.value)}
+                placeholder="Leave blank to keep the saved one"
+                autoComplete="off"
+              />
+            </div>
+          </div>
+          <div className="flex gap-2">
+            <Button variant="outline" onClick={saveCaldavSettings}>
+              Save
+            </Button>
+            <Button
+              variant="outline"
+              onClick={testCaldavConnection}
+              disabled={!caldavUrl || isTestingCaldav}
+            >
+              {isTestingCaldav ? "Testing..." : "Test Connection"}
+            </Button>
+          </div>
+        </div>
+
+        {/* Browser Profiles Section */}
+        <div className="space-y-4 pt-4">
+          <div>
+            <h2 className="text-lg font-semibold">Browser Profiles</h2>
+            <p className="text-sm text-muted-foreground mt-1">
+              History from enabled profiles in Zen, Chrome, Arc, Brave, Edge
+              and Safari is synced. A label such as "work" or "personal" is
+              recorded on every visit from that profile. Reading Safari history
+              needs Full Disk Access.
+            </p>
+          </div>
+          <div className="space-y-2">
+            {browserProfiles.map((profile) => (
+              <div key={profile.path} className="flex items-center gap-2">
+                <label
+                  className="flex flex-1 items-center gap-2 text-sm"
+                  title={profile.path}
+                >
+                  <input
+                    type="checkbox"
+                    checked={profile.enabled}
+                    onChange={(e) =>
+                      updateBrowserProfile(profile.path, {
+                        enabled: e.target.checked,
+                      })
+                    }
+                  />
+                  {BROWSER_NAMES[profile.browser]} – {profile.name}
+                </label>
+                <Input
+                  defaultValue={profile.label ?? ""}
+                  placeholder="Label"
+                  className="w-40"
+                  onBlur={(e) => {
+                    const label = e.target.value.trim() || null;
+                    if (label !== (profile.label ?? null)) {
+                      updateBrowserProfile(profile.path, { label });
+                    }
+                  }}
+                />
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  onClick={() =>
+                    saveBrowserProfiles(
+                      browserProfiles.filter((p) => p.path !== profile.path)
+                    )
+                  }
+                >
+                  <X className="h-4 w-4" />
+                </Button>
+              </div>
+            ))}
+            {browserProfiles.length === 0 && (
               <p className="text-xs text-muted-foreground">
-                Click "Auto-detect" to find your Zen profile automatically, or
-                browse to select it manually.
+                No browser profiles configured yet.
               </p>
             )}
           </div>
+          <div className="space-y-2">
+            <Label>Found on this Mac</Label>
+            {discoveredProfiles
+              .filter((d) => !browserProfiles.some((p) => p.path === d.path))
+              .map((profile) => (
+                <div
+                  key={profile.path}
+                  className="flex items-center justify-between text-sm"
+                  title={profile.path}
+                >
+                  {BROWSER_NAMES[profile.browser]} – {profile.name}
+                  <Button
+                    variant="outline"
+                    size="sm"
+                    onClick={() =>
+                      saveBrowserProfiles([...browserProfiles, profile])
+                    }
+                  >
+                    <Plus className="mr-2 h-4 w-4" />
+                    Add
+                  </Button>
+                </div>
+              ))}
+            <Button variant="outline" onClick={browseBrowserProfile}>
+              Browse...
+            </Button>
+          </div>
+        </div>
+
+        {/* Browser Privacy Filter Section */}
+        <div className="space-y-4 pt-4">
+          <div>
+            <h2 className="text-lg font-semibold">Browser Privacy Filter</h2>
+            <p className="text-sm text-muted-foreground mt-1">
+              Visits matching a deny rule or one of the default patterns are
+              never synced. Allow rules take precedence over both. Globs use *
+              as a wildcard; regexes can match anywhere in the value. Neither
+              cares about case.
+            </p>
+          </div>
+          <div className="space-y-2">
+            {filterRules.map((rule) => (
+              <div
+                key={rule.id}
+                className="flex items-center justify-between p-2 bg-muted/50 rounded-md"
+              >
+                <span className="text-sm">
+                  {rule.action === "allow" ? "Allow" : "Deny"} {rule.field}{" "}
+                  {rule.kind}{" "}
+                  <span className="font-mono">{rule.pattern}</span>
+                </span>
+                <div className="flex gap-1">
+                  <Button
+                    variant="ghost"
+                    size="sm"
+                    disabled={isPreviewingFilter}
+                    onClick={() =>
+                      previewFilterRules(
+                        filterRules.filter((r) => r.id !== rule.id)
+                      )
+                    }
+                  >
+                    Preview removal
+                  </Button>
+                  <Button
+                    variant="ghost"
+                    size="icon"
+                    onClick={() =>
+                      rule.id !== null && deleteFilterRule(rule.id)
+                    }
+                  >
+                    <X className="h-4 w-4" />
+                  </Button>
+                </div>
+              </div>
+            ))}
+          </div>
+          <div className="flex gap-2">
+            <Select
+              value={newFilterRule.action}
+              onValueChange={(action) =>
+                setNewFilterRule({
+                  ...newFilterRule,
+                  action: action as BrowserFilterRule["action"],
+                })
+              }
+            >
+              <SelectTrigger className="w-24">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="deny">Deny</SelectItem>
+                <SelectItem value="allow">Allow</SelectItem>
+              </SelectContent>
+            </Select>
+            <Select
+              value={newFilterRule.field}
+              onValueChange={(field) =>
+                setNewFilterRule({
+                  ...newFilterRule,
+                  field: field as BrowserFilterRule["field"],
+                })
+              }
+            >
+              <SelectTrigger className="w-28">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="url">URL</SelectItem>
+                <SelectItem value="domain">Domain</SelectItem>
+                <SelectItem value="title">Title</SelectItem>
+              </SelectContent>
+            </Select>
+            <Select
+              value={newFilterRule.kind}
+              onValueChange={(kind) =>
+                setNewFilterRule({
+                  ...newFilterRule,
+                  kind: kind as BrowserFilterRule["kind"],
+                })
+              }
+            >
+              <SelectTrigger className="w-24">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="glob">Glob</SelectItem>
+                <SelectItem value="regex">Regex</SelectItem>
+              </SelectContent>
+            </Select>
+            <Input
+              value={newFilterRule.pattern}
+              onChange={(e) =>
+                setNewFilterRule({ ...newFilterRule, pattern: e.target.value })
+              }
+              placeholder="e.g., *.bank.com"
+              className="flex-1 font-mono"
+            />
+          </div>
+          <div className="flex gap-2">
+            <Button
+              variant="outline"
+              disabled={!newFilterRule.pattern.trim() || isPreviewingFilter}
+              onClick={() =>
+                previewFilterRules([...filterRules, newFilterRule])
+              }
+            >
+              {isPreviewingFilter ? "Previewing..." : "Preview"}
+            </Button>
+            <Button
+              onClick={addFilterRule}
+              disabled={!newFilterRule.pattern.trim()}
+            >
+              <Plus className="mr-2 h-4 w-4" />
+              Add Rule
+            </Button>
+          </div>
+          {filterPreview && (
+            <div className="space-y-1 text-sm">
+              <p className="text-muted-foreground">
+                {filterPreview.included_count} of {filterPreview.sample_size}{" "}
+                recently visited pages would be synced;{" "}
+                {filterPreview.changes.length} would change.
+              </p>
+              {filterPreview.changes.map((change) => (
+                <div
+                  key={change.visit.url}
+                  className="p-2 bg-muted/50 rounded-md"
+                  title={change.visit.url}
+                >
+                  <div className="truncate">
+                    {change.visit.title || change.visit.url}
+                  </div>
+                  <div className="text-xs text-muted-foreground">
+                    {describeDecision(change.before)} →{" "}
+                    {describeDecision(change.after)}
+                  </div>
+                </div>
+              ))}
+            </div>
+          )}
+          <details className="text-sm">
+            <summary className="cursor-pointer text-muted-foreground">
+              Default patterns ({defaultFilters.length})
+            </summary>
+            <div className="mt-2 space-y-1 font-mono text-xs">
+              {defaultFilters.map((pattern) => (
+                <div key={pattern}>{pattern}</div>
+              ))}
+            </div>
+          </details>
         </div>
 
         {/* GitHub Organizations Section */}
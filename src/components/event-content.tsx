@@ -17,6 +17,7 @@ import {
   Globe,
   KanbanSquare,
   type LucideIcon,
+  StickyNote,
 } from "lucide-react";
 import type { ComponentType } from "react";
 import { useEffect, useState } from "react";
@@ -49,6 +50,8 @@ export function getEventIcon(event: UIEvent): IconComponent {
       return GitHubIcon;
     case "research":
       return Globe;
+    case "note":
+      return StickyNote;
     default:
       throw new Error();
   }
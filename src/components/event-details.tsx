@@ -1,6 +1,10 @@
 import { formatEventTime } from "@/components/calendar-utils";
 import type { UIEvent } from "@/types/event";
-import { parseCalendarEventData } from "@/types/event";
+import {
+  parseBrowserEventData,
+  parseCalendarEventData,
+  parseNoteEventData,
+} from "@/types/event";
 import type { ReactNode } from "react";
 
 interface EventDetailsProps {
@@ -26,6 +30,9 @@ export function EventDetails({ event }: EventDetailsProps) {
   if (event.type === "calendar") {
     return <CalendarEventDetails event={event} />;
   }
+  if (event.type === "note") {
+    return <NoteEventDetails event={event} />;
+  }
   return <ActivityEventDetails event={event} />;
 }
 
@@ -36,8 +43,20 @@ interface CalendarEventDetailsProps {
 function CalendarEventDetails({ event }: CalendarEventDetailsProps) {
   const calendarEvent = event.activities[0];
   const calendarData = parseCalendarEventData(calendarEvent);
+  const statusLabel =
+    calendarData?.status === "cancelled"
+      ? "Cancelled"
+      : calendarData?.participant_status === "declined"
+        ? "Declined"
+        : null;
   return (
     <div className="space-y-4">
+      {statusLabel && (
+        <DetailsSection title="Status">
+          <span className="text-destructive">{statusLabel}</span>
+        </DetailsSection>
+      )}
+
       {calendarData?.location && (
         <DetailsSection title="Location">
           {calendarData.location}
@@ -67,6 +86,19 @@ function CalendarEventDetails({ event }: CalendarEventDetailsProps) {
     </div>
   );
 }
+
+function NoteEventDetails({ event }: { event: UIEvent }) {
+  const noteData = parseNoteEventData(event.activities[0]);
+  if (!noteData?.body) {
+    return null;
+  }
+  return (
+    <DetailsSection title="Note">
+      <div className="whitespace-pre-wrap">{noteData.body}</div>
+    </DetailsSection>
+  );
+}
+
 interface ActivityEventDetailsProps {
   event: UIEvent;
 }
@@ -93,6 +125,11 @@ function ActivityEventDetails({ event }: ActivityEventDetailsProps) {
                 ) : (
                   <div className="text-muted-foreground">{item.title}</div>
                 )}
+                {parseBrowserEventData(item)?.profile_label && (
+                  <span className="ml-2 shrink-0 text-xs text-muted-foreground">
+                    {parseBrowserEventData(item)?.profile_label}
+                  </span>
+                )}
               </div>
             ))}
           </div>
@@ -6,6 +6,12 @@ import { Label } from "@/components/ui/label";
 import type { Project, ProjectRule, StoredEvent } from "../types/event";
 import { parseCalendarEventData } from "../types/event";
 
+type FormRuleType =
+  | "organizer"
+  | "title_pattern"
+  | "repository"
+  | "recurring_series";
+
 interface RuleFormProps {
   project?: Project | null;
   rule?: ProjectRule | null;
@@ -27,7 +33,7 @@ export function RuleForm({
 }: RuleFormProps) {
   const [formData, setFormData] = useState({
     projectId: project?.id || (null as number | null),
-    ruleType: "title_pattern" as "organizer" | "title_pattern" | "repository",
+    ruleType: "title_pattern" as FormRuleType,
     matchValue: "",
   });
   const [error, setError] = useState<string | null>(null);
@@ -37,16 +43,19 @@ export function RuleForm({
     if (rule) {
       setFormData({
         projectId: rule.project_id,
-        ruleType: rule.rule_type as
-          | "organizer"
-          | "title_pattern"
-          | "repository",
+        ruleType: rule.rule_type as FormRuleType,
         matchValue: rule.match_value,
       });
     } else if (event) {
       const eventData = parseCalendarEventData(event);
 
-      if (eventData?.organizer) {
+      if (event.series_id) {
+        setFormData({
+          projectId: project?.id || null,
+          ruleType: "recurring_series",
+          matchValue: event.series_id,
+        });
+      } else if (eventData?.organizer) {
         setFormData({
           projectId: project?.id || null,
           ruleType: "organizer",
@@ -141,10 +150,7 @@ export function RuleForm({
           onChange={(e) =>
             setFormData({
               ...formData,
-              ruleType: e.target.value as
-                | "organizer"
-                | "title_pattern"
-                | "repository",
+              ruleType: e.target.value as FormRuleType,
             })
           }
           disabled={saving}
@@ -152,6 +158,9 @@ export function RuleForm({
           <option value="title_pattern">Title contains text</option>
           <option value="organizer">Organizer email</option>
           <option value="repository">Git repository</option>
+          {(event?.series_id || formData.ruleType === "recurring_series") && (
+            <option value="recurring_series">This recurring series</option>
+          )}
         </select>
       </div>
       <div className="space-y-2">
@@ -159,6 +168,7 @@ export function RuleForm({
           {formData.ruleType === "organizer" && "Organizer Email"}
           {formData.ruleType === "title_pattern" && "Text to Match"}
           {formData.ruleType === "repository" && "Repository Path"}
+          {formData.ruleType === "recurring_series" && "Series ID"}
         </Label>
         <Input
           id="match-value"
@@ -170,6 +180,7 @@ export function RuleForm({
                 : "facebook/react"
           }
           value={formData.matchValue}
+          readOnly={formData.ruleType === "recurring_series"}
           onChange={(e) =>
             setFormData({
               ...formData,
@@ -63,6 +63,8 @@ export function useSyncManager() {
               newProgress.set(payload.source, {
                 new: payload.new_events,
                 updated: payload.updated_events,
+                assigned: payload.auto_assigned_events,
+                removed: payload.removed_events,
                 status: "completed",
               });
               return { ...prev, progress: newProgress };
@@ -0,0 +1,33 @@
+import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
+
+// Must match Rust definitions in export.rs
+export interface ExportRequest {
+  content: "events" | "project_time";
+  format: "csv" | "json_lines" | "timesheet";
+  start_date: string; // YYYY-MM-DD
+  end_date: string; // YYYY-MM-DD, inclusive
+  time_source?: "entries" | "estimated";
+}
+
+const extensions: Record<ExportRequest["format"], string> = {
+  csv: "csv",
+  json_lines: "jsonl",
+  timesheet: "csv",
+};
+
+// Ask where to save, then write the export there.
+// Returns the number of rows written, or null if the dialog was cancelled.
+export async function exportWithSaveDialog(
+  request: ExportRequest
+): Promise<number | null> {
+  const extension = extensions[request.format];
+  const path = await save({
+    defaultPath: `traceback-${request.content}-${request.start_date}-to-${request.end_date}.${extension}`,
+    filters: [{ name: extension.toUpperCase(), extensions: [extension] }],
+  });
+  if (!path) {
+    return null;
+  }
+  return invoke<number>("export_data", { path, request });
+}
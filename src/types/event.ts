@@ -13,10 +13,20 @@ export interface StoredEvent {
   organizer_id?: number;
   repository_path?: string;
   domain?: string;
+  series_id?: string; // Shared by every occurrence of a recurring calendar event
+  assignment_source: AssignmentSource;
+  assignment_rule_id?: number;
   created_at: string;
   updated_at: string;
 }
 
+export type AssignmentSource = "unassigned" | "manual" | "rule";
+
+export interface RuleApplicationResult {
+  assigned_count: number;
+  pinned_event_ids: number[]; // Matched by a rule but kept their manual assignment
+}
+
 export interface Contact {
   id: number;
   name: string;
@@ -40,18 +50,223 @@ export interface ProjectRule {
     | "title_pattern"
     | "repository"
     | "url_pattern"
-    | "domain";
-  match_value: string;
+    | "domain"
+    | "attendee"
+    | "attendee_count"
+    | "calendar"
+    | "branch_pattern"
+    | "ticket_prefix"
+    | "git_activity"
+    | "recurring_series"
+    | "condition";
+  match_value: string; // JSON-encoded RuleCondition for "condition" rules
+  condition?: RuleCondition;
   priority: number;
   created_at: string;
 }
 
+// Structured rule condition tree - must match Rust definitions in rules.rs
+export type RuleCondition =
+  | { type: "all"; conditions: RuleCondition[] }
+  | { type: "any"; conditions: RuleCondition[] }
+  | { type: "not"; condition: RuleCondition }
+  | { type: "event_type"; event_types: string[] }
+  | { type: "field"; field: RuleField; matcher: TextMatcher }
+  | { type: "all_day" }
+  | { type: "attendee_count"; min: number }
+  // Evaluated in the user's local time zone; times are "HH:MM", weekdays "Mon".."Sun"
+  | {
+      type: "local_time";
+      weekdays?: Weekday[];
+      after?: string;
+      before?: string;
+    };
+
+export type Weekday = "Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun";
+
+export type RuleField =
+  | "title"
+  | "organizer"
+  | "url"
+  | "domain"
+  | "repository_path"
+  | "attendee"
+  | "calendar"
+  | "calendar_account"
+  | "branch"
+  | "commit_message"
+  | "activity_type"
+  | "series_id";
+
+export type TextMatcher =
+  | { op: "equals"; value: string }
+  | { op: "like"; pattern: string }
+  | { op: "glob"; pattern: string }
+  | { op: "regex"; pattern: string; case_insensitive?: boolean };
+
+export type PreviewStatus =
+  | { type: "claimed" }
+  | {
+      type: "claimed_by_higher_priority";
+      rule_id: number | null;
+      project_id: number;
+    }
+  | { type: "pinned" };
+
+export interface RulePreview {
+  match_count: number;
+  claimed_count: number;
+  matches: {
+    event: StoredEvent;
+    current_rule_id: number | null;
+    status: PreviewStatus;
+  }[];
+}
+
+// Candidate rule mined from manual assignments (rule.id is unset until saved)
+export interface RuleSuggestion {
+  rule: Omit<ProjectRule, "id"> & { id: number | null };
+  confidence: number;
+  supporting_event_count: number;
+  conflicting_event_count: number;
+  preview: RulePreview;
+}
+
+// Project/rule import - must match Rust definitions in taxonomy.rs
+export interface ImportOptions {
+  on_project_conflict?: "merge" | "skip" | "rename";
+  on_rule_conflict?: "keep" | "replace";
+  dry_run?: boolean;
+}
+
+export type ProjectChange =
+  | { action: "create"; name: string }
+  | { action: "merge"; name: string; project_id: number }
+  | { action: "skip"; name: string; project_id: number }
+  | { action: "rename"; name: string; new_name: string };
+
+export type RuleChange =
+  | { action: "create" }
+  | { action: "unchanged" }
+  | { action: "keep"; existing_project_id: number }
+  | { action: "replace"; rule_id: number; previous_project_id: number }
+  | { action: "skipped_project" }
+  | { action: "invalid"; error: string };
+
+export interface ImportReport {
+  dry_run: boolean;
+  projects: ProjectChange[];
+  rules: {
+    project_name: string;
+    rule_type: string;
+    match_value: string;
+    change: RuleChange;
+  }[];
+}
+
+// Daily time ledger - must match Rust definitions in db.rs / ledger.rs
+export interface TimeEntry {
+  id: number;
+  date: string; // YYYY-MM-DD (local day)
+  project_id: number;
+  duration_minutes: number;
+  note?: string;
+  event_ids: number[];
+  created_at: string;
+  updated_at: string;
+}
+
+export type Period = "day" | "week" | "month";
+
+export interface TimeLedger {
+  period: Period;
+  start: string;
+  end: string; // Inclusive
+  entries: TimeEntry[];
+  project_totals: { project_id: number; minutes: number }[];
+  total_minutes: number;
+}
+
+// Full-text search - must match Rust definitions in db.rs
+export interface SearchFilters {
+  event_types?: string[];
+  project_id?: number;
+  start_date?: string; // RFC3339
+  end_date?: string; // RFC3339
+  repository_path?: string;
+  domain?: string;
+  limit?: number;
+}
+
+export interface SearchResult {
+  event: StoredEvent;
+  snippet: string; // Matches wrapped in ** **
+  rank: number; // Lower is better
+}
+
+// Period reports - must match Rust definitions in reports.rs
+export interface Totals {
+  event_count: number;
+  duration_seconds: number;
+}
+
+export interface AggregateEntry {
+  key: string | null; // Project name, event type, repository path or domain
+  project_id: number | null;
+  current: Totals;
+  previous: Totals;
+  delta: Totals;
+}
+
+export interface PeriodReport {
+  period: Period;
+  start: string;
+  end: string;
+  previous_start: string;
+  previous_end: string;
+  by_project: AggregateEntry[];
+  by_event_type: AggregateEntry[];
+  by_repository_path: AggregateEntry[];
+  by_domain: AggregateEntry[];
+}
+
+export interface RecurringSeries {
+  series_id: string;
+  title: string; // Title of the latest occurrence
+  project_id: number | null; // Project of the latest occurrence
+  totals: Totals;
+  months: { month: string; totals: Totals }[]; // "YYYY-MM", oldest first
+}
+
+// Estimated time - must match Rust definitions in estimate.rs
+export interface TimeBlock {
+  project_id: number | null;
+  source: "calendar" | "git" | "browser";
+  start: number; // Unix timestamp in seconds
+  end: number;
+  event_ids: number[];
+}
+
+export interface DayEstimate {
+  date: string; // YYYY-MM-DD
+  blocks: TimeBlock[];
+  project_totals: { project_id: number | null; minutes: number }[];
+  total_minutes: number;
+}
+
+export type EventStatus = "confirmed" | "tentative" | "cancelled";
+export type ParticipantStatus = "pending" | "accepted" | "tentative" | "declined";
+
 export interface CalendarEventData {
   location?: string;
   notes?: string;
   is_all_day: boolean;
   organizer?: string;
   attendees?: string[];
+  calendar_name?: string;
+  calendar_account?: string;
+  status?: EventStatus;
+  participant_status?: ParticipantStatus; // My response, if I was invited
 }
 
 export interface GitEventData {
@@ -70,6 +285,20 @@ export interface BrowserHistoryEventData {
   page_title?: string;
   visit_count: number;
   repository_path?: string; // Canonical org/repo path if this is a code repo visit
+  profile_label?: string; // Label of the browser profile the visit came from
+}
+
+export interface NoteEventData {
+  body: string; // Markdown
+}
+
+// Input for create_note / update_note
+export interface NoteInput {
+  title: string;
+  body: string;
+  start_date: string; // RFC3339
+  duration_minutes?: number;
+  project_id?: number;
 }
 
 export interface WorkDomain {
@@ -116,6 +345,18 @@ export function parseGitEventData(event: StoredEvent): GitEventData | null {
   }
 }
 
+export function parseNoteEventData(event: StoredEvent): NoteEventData | null {
+  if (event.event_type !== "note" || !event.type_specific_data) {
+    return null;
+  }
+
+  try {
+    return JSON.parse(event.type_specific_data) as NoteEventData;
+  } catch {
+    return null;
+  }
+}
+
 export function parseBrowserEventData(
   event: StoredEvent
 ): BrowserHistoryEventData | null {
@@ -509,7 +750,7 @@ function aggregateRepositoryEvents(events: StoredEvent[]): {
 
 export interface UIEvent {
   id: string;
-  type: "calendar" | "repository" | "document" | "research";
+  type: "calendar" | "repository" | "document" | "research" | "note";
   title: string;
   start_date: string;
   end_date: string;
@@ -553,6 +794,19 @@ function calendarStoredEventToUIEvent(event: StoredEvent): UIEvent {
   };
 }
 
+function noteStoredEventToUIEvent(event: StoredEvent): UIEvent {
+  return {
+    id: `note-${event.id}`,
+    type: "note",
+    title: event.title,
+    start_date: event.start_date,
+    end_date: event.end_date,
+    project_id: event.project_id,
+    is_all_day: false,
+    activities: [event],
+  };
+}
+
 export function aggregateAllEvents(
   githubOrgs: string[],
   eventsData: StoredEvent[]
@@ -567,7 +821,15 @@ export function aggregateAllEvents(
   const calendarEvents = eventsData
     .filter((e) => e.event_type === "calendar")
     .map(calendarStoredEventToUIEvent);
-  return [...calendarEvents, ...repositoryEvents, ...browserEvents];
+  const noteEvents = eventsData
+    .filter((e) => e.event_type === "note")
+    .map(noteStoredEventToUIEvent);
+  return [
+    ...calendarEvents,
+    ...repositoryEvents,
+    ...browserEvents,
+    ...noteEvents,
+  ];
 }
 
 export function filterUIEventsByDay(
@@ -593,3 +855,51 @@ export function filterUIEventsByDay(
 
   return { allDayEvents, timedEvents };
 }
+
+// Browser profiles - must match Rust definitions in browser.rs
+export type BrowserKind =
+  | "zen"
+  | "chrome"
+  | "arc"
+  | "brave"
+  | "edge"
+  | "safari";
+
+export interface BrowserProfile {
+  browser: BrowserKind;
+  name: string; // As shown in the browser's profile menu, or the directory name
+  path: string; // Profile directory
+  enabled: boolean; // Disabled profiles stay configured but aren't synced
+  label: string | null; // Such as "work" or "personal"
+}
+
+export interface BrowserFilterRule {
+  id: number | null;
+  action: "allow" | "deny"; // Allow rules override deny rules and the defaults
+  field: "url" | "domain" | "title";
+  kind: "glob" | "regex";
+  pattern: string; // Matched ignoring case
+}
+
+export type FilterDecision =
+  | { type: "included" }
+  | { type: "allowed"; rule_id: number | null }
+  | { type: "denied"; rule_id: number | null }
+  | { type: "default_excluded"; pattern: string };
+
+export interface BrowserVisit {
+  url: string;
+  title: string | null;
+  visit_date: number; // Unix timestamp in microseconds
+  visit_count: number;
+}
+
+export interface FilterPreview {
+  sample_size: number;
+  included_count: number; // Sampled pages the proposed rules would ingest
+  changes: {
+    visit: BrowserVisit;
+    before: FilterDecision;
+    after: FilterDecision;
+  }[];
+}
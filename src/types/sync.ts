@@ -21,6 +21,8 @@ export type SyncEvent =
       source: SyncSource;
       new_events: number;
       updated_events: number;
+      auto_assigned_events: number;
+      removed_events: number; // Stored events no longer at the source
     }
   | {
       type: "completed";
@@ -41,7 +43,13 @@ export interface SyncState {
   currentSource: SyncSource | null;
   progress: Map<
     SyncSource,
-    { new: number; updated: number; status: ProgressStatus }
+    {
+      new: number;
+      updated: number;
+      assigned: number;
+      removed: number;
+      status: ProgressStatus;
+    }
   >;
   errors: Array<{ source?: SyncSource; error: string }>;
 }